@@ -0,0 +1,166 @@
+
+//! Hard Dollar WBS element lookup table
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{HdLookup, JobShipment, Wbs, WbsConversionError};
+
+/// Table of Hard Dollar WBS elements and the job and shipment they are for
+///
+/// The table can be loaded from a `.toml` file
+/// ```toml
+/// [[line]]
+/// wbs = "D-1234567-00123"
+/// job = "1234567A-1"
+/// ```
+/// or a `.csv` file, with an optional `wbs,job` header
+/// ```csv
+/// wbs,job
+/// D-1234567-00123,1234567A-1
+/// ```
+#[derive(Debug, Default)]
+pub struct HdTable {
+    lines: HashMap<(u32, u32), JobShipment>
+}
+
+#[derive(Debug, Deserialize)]
+struct HdTableFile {
+    #[serde(default)]
+    line: Vec<HdTableLine>
+}
+
+#[derive(Debug, Deserialize)]
+struct HdTableLine {
+    wbs: String,
+    job: String
+}
+
+impl HdTable {
+    /// load a table from a `.toml` or `.csv` file
+    pub fn load<T: Into<PathBuf>>(path: T) -> anyhow::Result<Self> {
+        let path = path.into();
+        let contents = fs::read_to_string(&path)?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::from_csv(&contents),
+            _ => Self::from_toml(&contents)
+        }
+    }
+
+    /// parse a table from TOML text
+    pub fn from_toml(contents: &str) -> anyhow::Result<Self> {
+        let file = toml::from_str::<HdTableFile>(contents)?;
+
+        let mut table = Self::default();
+        for line in file.line {
+            table.insert_str(&line.wbs, &line.job)?;
+        }
+
+        Ok(table)
+    }
+
+    /// parse a table from CSV text
+    pub fn from_csv(contents: &str) -> anyhow::Result<Self> {
+        let mut table = Self::default();
+        for (i, line) in contents.lines().enumerate() {
+            match line.split(',').map(str::trim).collect::<Vec<_>>().as_slice() {
+                [""] => continue,
+                [wbs, job] if i == 0 && wbs.eq_ignore_ascii_case("wbs") && job.eq_ignore_ascii_case("job") => continue,
+                [wbs, job] => table.insert_str(wbs, job)
+                    .map_err(|e| anyhow::anyhow!("line {}: {e}", i+1))?,
+                _ => anyhow::bail!("line {}: expected 2 columns `wbs,job`", i+1)
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// add a Hard Dollar line to the table
+    ///
+    /// returns the job and shipment the line previously mapped to, if any
+    pub fn insert(&mut self, project: u32, id: u32, job: JobShipment) -> Option<JobShipment> {
+        self.lines.insert((project, id), job)
+    }
+
+    /// number of lines in the table
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// if the table has no lines
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    fn insert_str(&mut self, wbs: &str, job: &str) -> crate::Result<()> {
+        match Wbs::parse_strict(wbs)? {
+            Wbs::Hd { project, id } => {
+                if let Some(prev) = self.insert(project, id, job.parse()?) {
+                    log::warn!("Hard Dollar WBS element `{wbs}` is listed more than once (replacing `{prev}` with `{job}`)");
+                }
+
+                Ok(())
+            },
+            legacy => Err(WbsConversionError::NotHardDollar(legacy).into())
+        }
+    }
+}
+
+impl HdLookup for HdTable {
+    fn lookup(&self, project: u32, id: u32) -> Option<JobShipment> {
+        self.lines.get(&(project, id)).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let table = HdTable::from_toml(r#"
+            [[line]]
+            wbs = "D-1234567-00123"
+            job = "1234567A-1"
+
+            [[line]]
+            wbs = "D-1234567-00124"
+            job = "1234567B-2"
+        "#).unwrap();
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.lookup(1234567, 124), Some(JobShipment::new(1234567, 'B', 2)));
+        assert_eq!(table.lookup(1234567, 125), None);
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let table = HdTable::from_csv("wbs,job\nD-1234567-00123, 1234567A-1\n\n").unwrap();
+
+        assert_eq!(table.len(), 1);
+        assert_eq!(
+            Wbs::Hd { project: 1234567, id: 123 }.job_shipment(&table).unwrap(),
+            JobShipment::new(1234567, 'A', 1)
+        );
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        assert!(HdTable::from_csv("S-1234567-2-01,1234567A-1").is_err());
+        assert!(HdTable::from_csv("D-1234567-00123,1234567-1").is_err());
+        assert!(HdTable::from_csv("D-1234567-00123").is_err());
+    }
+
+    #[test]
+    fn test_insert_errors() {
+        use crate::Error;
+
+        let mut table = HdTable::default();
+        assert!(matches!(table.insert_str("D-123-00123", "1234567A-1"), Err(Error::Wbs(_))));
+        assert!(matches!(table.insert_str("D-1234567-00123", "1234567-1"), Err(Error::JobShipment(_))));
+        assert!(matches!(table.insert_str("S-1234567-2-01", "1234567A-1"), Err(Error::WbsConversion(WbsConversionError::NotHardDollar(_)))));
+    }
+}
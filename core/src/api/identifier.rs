@@ -0,0 +1,222 @@
+
+//! Identifier classification
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::LazyLock;
+
+use super::{JobShipment, MaterialMaster, Part, ProgramName, SheetName, Wbs};
+
+// heat numbers start with a digit and have at least one letter (i.e. `8A1234`)
+static HEAT_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^\d[[:alnum:]]{3,11}$").expect("failed to build HEAT_PATTERN regex"));
+// SAP purchase order numbers are 10 digits
+static PO_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^\d{10}$").expect("failed to build PO_PATTERN regex"));
+
+/// Kind of [`Identifier`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IdentifierKind {
+    /// Sigmanest program
+    Program,
+    /// Part mark
+    Part,
+    /// Sigmanest sheet
+    Sheet,
+    /// SAP material master
+    MaterialMaster,
+    /// SAP WBS element
+    Wbs,
+    /// Job and shipment
+    JobShipment,
+    /// Heat number
+    Heat,
+    /// Purchase order number
+    Po,
+}
+
+impl Display for IdentifierKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Program        => write!(f, "Program"),
+            Self::Part           => write!(f, "Part"),
+            Self::Sheet          => write!(f, "Sheet"),
+            Self::MaterialMaster => write!(f, "Material Master"),
+            Self::Wbs            => write!(f, "WBS element"),
+            Self::JobShipment    => write!(f, "Job-Shipment"),
+            Self::Heat           => write!(f, "Heat number"),
+            Self::Po             => write!(f, "PO number"),
+        }
+    }
+}
+
+/// A classified identifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identifier {
+    /// Sigmanest program
+    Program(ProgramName),
+    /// Part mark
+    Part(Part),
+    /// Sigmanest sheet
+    Sheet(SheetName),
+    /// SAP material master
+    MaterialMaster(MaterialMaster),
+    /// SAP WBS element
+    Wbs(Wbs),
+    /// Job and shipment
+    JobShipment(JobShipment),
+    /// Heat number
+    Heat(String),
+    /// Purchase order number
+    Po(String),
+}
+
+/// Error classifying an [`Identifier`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum ClassifyError {
+    /// Value does not match any kind of identifier
+    Unrecognized(String),
+    /// Value matches more than one kind of identifier
+    Ambiguous(String, Vec<Identifier>),
+}
+
+impl Display for ClassifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unrecognized(val) => write!(f, "`{val}` is not a recognized identifier"),
+            Self::Ambiguous(val, candidates) => {
+                let kinds = candidates.iter()
+                    .map(|id| id.kind().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(f, "`{val}` is ambiguous, it could be any of: {kinds}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClassifyError {}
+
+impl Identifier {
+    /// Classifies a value as exactly one kind of identifier
+    pub fn classify(value: &str) -> Result<Self, ClassifyError> {
+        log::trace!("Classifying identifier {value}");
+
+        let mut candidates = Self::candidates(value);
+        match candidates.len() {
+            0 => Err(ClassifyError::Unrecognized(value.into())),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(ClassifyError::Ambiguous(value.into(), candidates))
+        }
+    }
+
+    /// Every kind of identifier a value could be
+    pub fn candidates(value: &str) -> Vec<Self> {
+        let value = value.trim();
+        let mut candidates = Vec::new();
+
+        if let Ok(program) = value.parse() {
+            candidates.push(Self::Program(program));
+        }
+        if let Ok(part) = value.parse() {
+            candidates.push(Self::Part(part));
+        }
+        if let Ok(sheet) = value.parse() {
+            candidates.push(Self::Sheet(sheet));
+        }
+        if let Ok(mm) = value.parse() {
+            candidates.push(Self::MaterialMaster(mm));
+        }
+        if let Ok(wbs) = value.to_uppercase().parse::<Wbs>() {
+            candidates.push(Self::Wbs(wbs));
+        }
+        if let Ok(js) = value.parse() {
+            candidates.push(Self::JobShipment(js));
+        }
+        if HEAT_PATTERN.is_match(value) && value.chars().any(|c| c.is_ascii_alphabetic()) {
+            candidates.push(Self::Heat(value.to_uppercase()));
+        }
+        if PO_PATTERN.is_match(value) {
+            candidates.push(Self::Po(value.into()));
+        }
+
+        candidates
+    }
+
+    /// Kind of identifier
+    pub fn kind(&self) -> IdentifierKind {
+        match self {
+            Self::Program(_)        => IdentifierKind::Program,
+            Self::Part(_)           => IdentifierKind::Part,
+            Self::Sheet(_)          => IdentifierKind::Sheet,
+            Self::MaterialMaster(_) => IdentifierKind::MaterialMaster,
+            Self::Wbs(_)            => IdentifierKind::Wbs,
+            Self::JobShipment(_)    => IdentifierKind::JobShipment,
+            Self::Heat(_)           => IdentifierKind::Heat,
+            Self::Po(_)             => IdentifierKind::Po,
+        }
+    }
+}
+
+impl Display for Identifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Program(program) => write!(f, "{program}"),
+            Self::Part(part)       => write!(f, "{part}"),
+            Self::Sheet(sheet)     => write!(f, "{sheet}"),
+            Self::MaterialMaster(mm) => write!(f, "{mm}"),
+            Self::Wbs(wbs)         => write!(f, "{wbs}"),
+            Self::JobShipment(js)  => write!(f, "{js}"),
+            Self::Heat(heat)       => write!(f, "{heat}"),
+            Self::Po(po)           => write!(f, "{po}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind_of(value: &str) -> IdentifierKind {
+        Identifier::classify(value).unwrap().kind()
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(kind_of("52198"), IdentifierKind::Program);
+        assert_eq!(kind_of("52198-2A"), IdentifierKind::Program);
+        assert_eq!(kind_of("1234567A-X1A"), IdentifierKind::Part);
+        assert_eq!(kind_of("S12345"), IdentifierKind::Sheet);
+        assert_eq!(kind_of("50W-0008"), IdentifierKind::MaterialMaster);
+        assert_eq!(kind_of("1234567A01-05000"), IdentifierKind::MaterialMaster);
+        assert_eq!(kind_of("D-1234567-00123"), IdentifierKind::Wbs);
+        assert_eq!(kind_of("S-1234567-2-01"), IdentifierKind::Wbs);
+        assert_eq!(kind_of("8A1234"), IdentifierKind::Heat);
+        assert_eq!(kind_of("4500123456"), IdentifierKind::Po);
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        match Identifier::classify("1234567A-1") {
+            Err(ClassifyError::Ambiguous(_, candidates)) => assert_eq!(
+                candidates.iter().map(Identifier::kind).collect::<Vec<_>>(),
+                [IdentifierKind::Part, IdentifierKind::JobShipment]
+            ),
+            result => panic!("expected ambiguous classification, got {result:?}")
+        }
+    }
+
+    #[test]
+    fn test_shipment_overflow() {
+        // a shipment that does not fit in a u32 is not a job-shipment, rather than a panic
+        let candidates = Identifier::candidates("1234567A-99999999999");
+        assert!(candidates.iter().all(|id| id.kind() != IdentifierKind::JobShipment), "{candidates:?}");
+    }
+
+    #[test]
+    fn test_unrecognized() {
+        assert_eq!(
+            Identifier::classify("not an id"),
+            Err(ClassifyError::Unrecognized(String::from("not an id")))
+        );
+    }
+}
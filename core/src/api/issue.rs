@@ -0,0 +1,177 @@
+
+//! SAP issue codes for material that is not consumed to a production order
+
+use std::fmt::{self, Display, Formatter};
+
+use super::{ShipmentKind, Wbs};
+
+/// SAP issue code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IssueCode {
+    /// Cost center issue, from stock material
+    Cc01,
+    /// Cost center issue, from project material
+    Cc02,
+    /// Project issue, from material for the same project
+    Pr01,
+    /// Project issue, from stock material
+    Pr02,
+    /// Project issue, from material for a different project
+    Pr03,
+}
+
+/// `Code`, `User1` and `User2` fields of an issue record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// Issue code
+    pub code: IssueCode,
+    /// Cost center (cost center issues) or project WBS prefix (project issues)
+    pub user1: String,
+    /// GL account (cost center issues) or shipment (project issues)
+    pub user2: String,
+}
+
+/// Rules for issuing parts to a cost center
+pub trait CostCenterRules {
+    /// GL account to issue a part to
+    fn account(&self, part: &str) -> &str;
+
+    /// cost center for a year shipment (i.e. `2024`), if it is not the shipment itself
+    fn cost_center(&self, shipment: &str) -> Option<&str>;
+}
+
+impl IssueCode {
+    /// Classifies the issue of a part
+    ///
+    /// `job` and `shipment` are the part's job and shipment, as they are in Sigmanest.
+    /// Parts with a shipment of `20xx` are cut for a cost center, which is looked up in `rules`.
+    /// `sheet_wbs` is the WBS element of the sheet the part was cut from (empty for stock material).
+    pub fn classify(part: &str, job: &str, shipment: &str, sheet_wbs: &str, rules: &impl CostCenterRules) -> Issue {
+        let sheet_wbs = sheet_wbs.trim();
+
+        if is_cost_center(shipment) {
+            let code = match sheet_wbs.is_empty() {
+                true  => Self::Cc01,
+                false => Self::Cc02,
+            };
+
+            let cost_center = rules.cost_center(shipment).unwrap_or(shipment);
+
+            return Issue { code, user1: cost_center.into(), user2: rules.account(part).into() };
+        }
+
+        let code = match sheet_wbs.to_uppercase().parse::<Wbs>() {
+            Ok(Wbs::Hd { project, .. }) if format!("{project:07}") == job => Self::Pr01,
+            _ if sheet_wbs.is_empty() => Self::Pr02,
+            // sheet is for a different job (or is not a Hard Dollar WBS element)
+            _ => Self::Pr03,
+        };
+
+        Issue { code, user1: format!("D-{job}"), user2: shipment.into() }
+    }
+
+    /// if this is a cost center issue
+    pub fn is_cost_center(&self) -> bool {
+        matches!(self, Self::Cc01 | Self::Cc02)
+    }
+}
+
+impl Display for IssueCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cc01 => write!(f, "CC01"),
+            Self::Cc02 => write!(f, "CC02"),
+            Self::Pr01 => write!(f, "PR01"),
+            Self::Pr02 => write!(f, "PR02"),
+            Self::Pr03 => write!(f, "PR03"),
+        }
+    }
+}
+
+/// cost center shipments are `20xx` (exactly 4 digits)
+fn is_cost_center(shipment: &str) -> bool {
+    shipment.len() == 4
+        && shipment.bytes().all(|b| b.is_ascii_digit())
+        && shipment.parse().map(ShipmentKind::of) == Ok(ShipmentKind::CostCenter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Rules;
+
+    impl CostCenterRules for Rules {
+        fn account(&self, part: &str) -> &str {
+            match part.to_lowercase().contains("gemini") {
+                true  => "634124",
+                false => "637118",
+            }
+        }
+
+        fn cost_center(&self, shipment: &str) -> Option<&str> {
+            match shipment {
+                "2023" => Some("CC2023"),
+                _ => None
+            }
+        }
+    }
+
+    fn classify(part: &str, shipment: &str, sheet_wbs: &str) -> Issue {
+        IssueCode::classify(part, "1234567", shipment, sheet_wbs, &Rules)
+    }
+
+    #[test]
+    fn test_cost_center_stock() {
+        assert_eq!(
+            classify("GEMINI-BRACKET", "2024", ""),
+            Issue { code: IssueCode::Cc01, user1: String::from("2024"), user2: String::from("634124") }
+        );
+    }
+
+    #[test]
+    fn test_cost_center_project_material() {
+        assert_eq!(
+            classify("SHELF_1", "2024", "D-1234567-00123"),
+            Issue { code: IssueCode::Cc02, user1: String::from("2024"), user2: String::from("637118") }
+        );
+    }
+
+    #[test]
+    fn test_cost_center_lookup() {
+        assert_eq!(classify("SHELF_1", "2023", "").user1, "CC2023");
+    }
+
+    #[test]
+    fn test_project_same_job() {
+        assert_eq!(
+            classify("X1A", "01", "D-1234567-00123"),
+            Issue { code: IssueCode::Pr01, user1: String::from("D-1234567"), user2: String::from("01") }
+        );
+    }
+
+    #[test]
+    fn test_project_stock() {
+        assert_eq!(classify("X1A", "01", " ").code, IssueCode::Pr02);
+    }
+
+    #[test]
+    fn test_project_different_job() {
+        assert_eq!(classify("X1A", "01", "D-7654321-00123").code, IssueCode::Pr03);
+        assert_eq!(classify("X1A", "01", "S-1234567-2-01").code, IssueCode::Pr03);
+        assert_eq!(classify("X1A", "01", "not a wbs").code, IssueCode::Pr03);
+    }
+
+    #[test]
+    fn test_not_cost_center() {
+        // only 4-digit `20xx` shipments are cost centers
+        for shipment in ["20", "201", "20245", "2A24", "1999"] {
+            assert!(!classify("X1A", shipment, "").code.is_cost_center());
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(IssueCode::Pr03.to_string(), "PR03");
+    }
+}
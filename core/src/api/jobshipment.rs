@@ -5,11 +5,44 @@ use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 use std::sync::LazyLock;
 
+/// most shipments a list can have, so that a mistyped range does not allocate millions of shipments
+const MAX_LIST_LEN: usize = 1000;
+
 static PATTERN_DESC: &str = "{7-digit number}{single letter}-{number}";
+static LIST_PATTERN_DESC: &str = "{7-digit number}{single letter}-{number or range}[,{number or range}...]";
 static JOB_SHIPMENT_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^(\d{7})([[:alpha:]]?)-(\d+)$").expect("failed to build JOB_SHIPMENT_PATTERN regex"));
+static JOB_SHIPMENT_LIST_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^(\d{7})([[:alpha:]]?)-(\d+(?:\.\.\d+)?(?:,\d+(?:\.\.\d+)?)*)$").expect("failed to build JOB_SHIPMENT_LIST_PATTERN regex"));
+
+/// Kind of shipment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShipmentKind {
+    /// Shipment of a job
+    Regular,
+    /// Year-based shipment (`20xx`) for parts cut for a cost center
+    CostCenter,
+}
+
+impl ShipmentKind {
+    /// Kind of a shipment number
+    pub fn of(shipment: u32) -> Self {
+        match shipment {
+            2000..=2099 => Self::CostCenter,
+            _ => Self::Regular
+        }
+    }
+}
+
+impl Display for ShipmentKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Regular    => write!(f, "Shipment"),
+            Self::CostCenter => write!(f, "Cost center"),
+        }
+    }
+}
 
 /// Job number (with structure letter) and shipment
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct JobShipment {
     /// Job Number
     job: u32,
@@ -19,13 +52,23 @@ pub struct JobShipment {
     shipment: u32
 }
 
+/// Error parsing a [`JobShipment`]
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum JobShipmentParseError {
     // InvalidJob(String),
-    // InvalidShipment(String),
+    /// Job is missing the structure letter
     MissingStructureLetter(String),
+    /// Job-Shipment does not match the expected pattern
     ExpectedPatternMismatch(String),
+    /// Job-Shipment list does not match the expected pattern
+    ExpectedListPatternMismatch(String),
+    /// Shipment range ends before it starts
+    InvalidRange(String),
+    /// Shipment is too large to be a shipment number
+    InvalidShipment(String),
+    /// Job-Shipment list has more shipments than [`JobShipment::parse_list`] allows
+    TooManyShipments(String),
 }
 
 impl Display for JobShipmentParseError {
@@ -33,10 +76,91 @@ impl Display for JobShipmentParseError {
         match self {
             Self::MissingStructureLetter(val) => write!(f, "Job-Shipment <{val}> is missing the structure letter"),
             Self::ExpectedPatternMismatch(val) => write!(f, "Job-Shipment <{val}> does not match expected pattern `{PATTERN_DESC}`"),
+            Self::ExpectedListPatternMismatch(val) => write!(f, "Job-Shipments <{val}> do not match expected pattern `{LIST_PATTERN_DESC}`"),
+            Self::InvalidRange(val) => write!(f, "Shipment range <{val}> ends before it starts"),
+            Self::InvalidShipment(val) => write!(f, "Shipment <{val}> is not a valid shipment number"),
+            Self::TooManyShipments(val) => write!(f, "Job-Shipments <{val}> has more than {MAX_LIST_LEN} shipments"),
         }
     }
 }
 
+impl std::error::Error for JobShipmentParseError {}
+
+impl JobShipment {
+    /// create a job and shipment from its parts
+    pub fn new(job: u32, structure: char, shipment: u32) -> Self {
+        Self { job, structure: structure.to_ascii_uppercase(), shipment }
+    }
+
+    /// Job number
+    pub fn job(&self) -> u32 {
+        self.job
+    }
+
+    /// Structure letter
+    pub fn structure(&self) -> char {
+        self.structure
+    }
+
+    /// Shipment
+    pub fn shipment(&self) -> u32 {
+        self.shipment
+    }
+
+    /// Kind of shipment
+    pub fn kind(&self) -> ShipmentKind {
+        ShipmentKind::of(self.shipment)
+    }
+
+    /// if the shipment is a year-based cost center shipment
+    pub fn is_cost_center(&self) -> bool {
+        self.kind() == ShipmentKind::CostCenter
+    }
+
+    /// Parses a list of shipments for a job (i.e. `1234567A-1,3,5` or `1234567A-1..5`)
+    ///
+    /// Ranges (`{first}..{last}`) include the last shipment. A list can have at most 1000 shipments.
+    pub fn parse_list(value: &str) -> Result<Vec<Self>, JobShipmentParseError> {
+        log::trace!("Parsing JobShipment <list> {value}");
+
+        let value = value.trim();
+        let (_, [job, structure, shipments]) = match JOB_SHIPMENT_LIST_PATTERN.captures(value).map(|c| c.extract()) {
+            Some((full, [_, "", _])) => return Err(JobShipmentParseError::MissingStructureLetter(full.into())),
+            Some(caps) => caps,
+            None => return Err(JobShipmentParseError::ExpectedListPatternMismatch(value.into()))
+        };
+
+        // unwraps are safe here because the regex will assure that parse() does not fail
+        let job = job.parse().unwrap();
+        let structure = structure.chars().nth(0).unwrap();
+
+        // the regex assures these are digits, but they may not fit in a u32
+        let shipment = |ship: &str| ship.parse::<u32>()
+            .map_err(|_| JobShipmentParseError::InvalidShipment(ship.into()));
+
+        let mut list = Vec::new();
+        for item in shipments.split(',') {
+            let (first, last) = match item.split_once("..") {
+                Some((first, last)) => (shipment(first)?, shipment(last)?),
+                None => (shipment(item)?, shipment(item)?)
+            };
+
+            if last < first {
+                return Err(JobShipmentParseError::InvalidRange(item.into()));
+            }
+
+            // checked before the range is added, so that it is never allocated
+            if (last - first) as usize >= MAX_LIST_LEN - list.len() {
+                return Err(JobShipmentParseError::TooManyShipments(value.into()));
+            }
+
+            list.extend((first..=last).map(|shipment| Self::new(job, structure, shipment)));
+        }
+
+        Ok(list)
+    }
+}
+
 impl FromStr for JobShipment {
     type Err = JobShipmentParseError;
     
@@ -46,11 +170,13 @@ impl FromStr for JobShipment {
         match JOB_SHIPMENT_PATTERN.captures(value).map(|c| c.extract()) {
             Some((full, [_, "", _])) => Err(JobShipmentParseError::MissingStructureLetter(full.into())),
             Some((_, [job, structure, ship])) => {
-                
-                // is this needed? regex might handle parsing errors
+                // unwraps are safe here because the job is 7 digits and the structure letter is not empty
                 let job = job.parse().unwrap();
                 let structure = structure.to_uppercase().chars().nth(0).unwrap();
-                let shipment = ship.parse().unwrap();
+
+                // the regex assures the shipment is digits, but it may not fit in a u32
+                let shipment = ship.parse()
+                    .map_err(|_| JobShipmentParseError::InvalidShipment(ship.into()))?;
 
                 Ok ( Self { job, structure, shipment })
             },
@@ -59,17 +185,23 @@ impl FromStr for JobShipment {
     }
 }
 
-impl From<String> for JobShipment {
-    fn from(value: String) -> Self {
-        log::trace!("Parsing JobShipment <From> {value}");
-        
-        Self::from_str(&value).unwrap()
+impl TryFrom<String> for JobShipment {
+    type Error = JobShipmentParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        log::trace!("Parsing JobShipment <TryFrom> {value}");
+
+        Self::from_str(&value)
     }
 }
 
+/// The alternate form (`{:#}`) names the kind of shipment (i.e. `1234567A cost center 2024`)
 impl Display for JobShipment {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}-{}", self.job, self.structure, self.shipment)
+        match f.alternate() {
+            true  => write!(f, "{}{} {} {}", self.job, self.structure, self.kind().to_string().to_lowercase(), self.shipment),
+            false => write!(f, "{}{}-{}", self.job, self.structure, self.shipment),
+        }
     }
 }
 
@@ -98,6 +230,70 @@ mod tests {
         );
     }
     
+    #[test]
+    fn test_shipment_kind() {
+        assert_eq!("1234567A-2024".parse::<JobShipment>().unwrap().kind(), ShipmentKind::CostCenter);
+        assert_eq!("1234567A-1".parse::<JobShipment>().unwrap().kind(), ShipmentKind::Regular);
+        assert_eq!(ShipmentKind::of(2100), ShipmentKind::Regular);
+
+        assert_eq!(format!("{:#}", JobShipment::new(1234567, 'A', 2024)), "1234567A cost center 2024");
+        assert_eq!(format!("{:#}", JobShipment::new(1234567, 'A', 1)), "1234567A shipment 1");
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let shipments = |value| JobShipment::parse_list(value).unwrap()
+            .iter()
+            .map(JobShipment::shipment)
+            .collect::<Vec<_>>();
+
+        assert_eq!(shipments("1234567A-1..5"), [1, 2, 3, 4, 5]);
+        assert_eq!(shipments("1234567a-1,3,7..8"), [1, 3, 7, 8]);
+        assert_eq!(shipments("1234567A-2"), [2]);
+        assert_eq!(JobShipment::parse_list("1234567b-1").unwrap()[0].structure(), 'B');
+    }
+
+    #[test]
+    fn test_parse_list_errors() {
+        assert_eq!(
+            JobShipment::parse_list("1234567A-5..1"),
+            Err(JobShipmentParseError::InvalidRange(String::from("5..1")))
+        );
+        assert_eq!(
+            JobShipment::parse_list("1234567-1..5"),
+            Err(JobShipmentParseError::MissingStructureLetter(String::from("1234567-1..5")))
+        );
+        assert_eq!(
+            JobShipment::parse_list("1234567A-1..,2"),
+            Err(JobShipmentParseError::ExpectedListPatternMismatch(String::from("1234567A-1..,2")))
+        );
+        assert_eq!(
+            JobShipment::parse_list("1234567A-1,99999999999"),
+            Err(JobShipmentParseError::InvalidShipment(String::from("99999999999")))
+        );
+    }
+
+    #[test]
+    fn test_parse_list_too_many() {
+        assert_eq!(JobShipment::parse_list("1234567A-1..1000").unwrap().len(), 1000);
+        assert_eq!(
+            JobShipment::parse_list("1234567A-1..4000000000"),
+            Err(JobShipmentParseError::TooManyShipments(String::from("1234567A-1..4000000000")))
+        );
+        assert_eq!(
+            JobShipment::parse_list("1234567A-1..999,1000,1001"),
+            Err(JobShipmentParseError::TooManyShipments(String::from("1234567A-1..999,1000,1001")))
+        );
+    }
+
+    #[test]
+    fn test_shipment_overflow() {
+        assert_eq!(
+            "1234567A-99999999999".parse::<JobShipment>(),
+            Err(JobShipmentParseError::InvalidShipment(String::from("99999999999")))
+        );
+    }
+
     #[test]
     fn test_expected_pattern_mismatch() {
         assert_eq!(
@@ -1,21 +1,316 @@
 
 //! Raw material types
 
-use super::Wbs;
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use super::{MaterialMaster, Wbs};
+use super::program::{cmp_suffixes, split_suffixes};
 
 // TODO: should sigmanest have its own api?
 
+static SHEET_PATTERN_DESC: &str = "{S, X or W}{5-digit number}[{- or _}{suffix}...]";
+static SHEET_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^([SXW])(\d{5})((?:[\-_][[:alnum:]]+)*)$").expect("failed to build SHEET_PATTERN regex"));
+
 /// Sigmanest sheet
-#[derive(Debug, Default)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sheet {
     /// Sheet name (id)
-    pub name: String,
+    pub name: SheetName,
     /// SAP Material Master
-    pub mm: String,
+    pub mm: Option<MaterialMaster>,
     /// Heat number
     pub heat: String,
     /// Purchase Order number
     pub po: String,
     /// SAP WBS element
-    pub wbs: Option<Wbs>
+    pub wbs: Option<Wbs>,
+    /// Sheet dimensions
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub size: Option<SheetSize>,
+    /// Area of the sheet that is not nested (square inches)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub remaining_area: Option<f64>
+}
+
+/// Sheet dimensions (in inches)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SheetSize {
+    /// Plate thickness
+    pub thickness: f64,
+    /// Sheet width
+    pub width: f64,
+    /// Sheet length
+    pub length: f64
+}
+
+impl SheetSize {
+    /// Total area of the sheet (square inches)
+    pub fn area(&self) -> f64 {
+        self.width * self.length
+    }
+}
+
+impl Display for SheetSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3} x {} x {}", self.thickness, self.width, self.length)
+    }
+}
+
+impl Sheet {
+    /// Sheet with only a name
+    pub fn new(name: SheetName) -> Self {
+        Self {
+            name,
+            mm: None,
+            heat: String::new(),
+            po: String::new(),
+            wbs: None,
+            size: None,
+            remaining_area: None
+        }
+    }
+
+    /// Total area of the sheet (square inches), if the size is known
+    pub fn total_area(&self) -> Option<f64> {
+        self.size.map(|size| size.area())
+    }
+
+    /// Area that has been nested (square inches), if the size and remaining area are known
+    pub fn nested_area(&self) -> Option<f64> {
+        Some(self.total_area()? - self.remaining_area?)
+    }
+
+    /// Fraction of the sheet area that `nested_area` uses, if the size is known
+    pub fn utilization(&self, nested_area: f64) -> Option<f64> {
+        self.total_area().map(|total| nested_area / total)
+    }
+
+    /// if `consumed_area` is more than the area of the sheet, if the size is known
+    pub fn exceeds_area(&self, consumed_area: f64) -> Option<bool> {
+        self.total_area().map(|total| consumed_area > total)
+    }
+}
+
+/// Kind of sheet, as determined by the sheet name prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SheetKind {
+    /// Stock sheet (`S` prefix)
+    Stock,
+    /// Remnant of a previously nested sheet (`W` prefix)
+    Remnant,
+    /// Any other sheet (`X` prefix)
+    Other
+}
+
+impl SheetKind {
+    /// sheet name prefix for this kind of sheet
+    pub fn prefix(&self) -> char {
+        match self {
+            Self::Stock   => 'S',
+            Self::Remnant => 'W',
+            Self::Other   => 'X',
+        }
+    }
+}
+
+impl Display for SheetKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stock   => write!(f, "Stock"),
+            Self::Remnant => write!(f, "Remnant"),
+            Self::Other   => write!(f, "Other"),
+        }
+    }
+}
+
+/// Sigmanest sheet name (i.e. `S12345` or `W12345-1`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SheetName {
+    /// Sheet kind (from the name prefix)
+    kind: SheetKind,
+    /// Sheet number
+    number: u32,
+    /// Suffixes (after a `-` or `_`)
+    suffixes: Vec<String>,
+    /// Sheet name, as given
+    name: String
+}
+
+/// Error parsing a [`SheetName`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum SheetNameParseError {
+    /// Sheet name does not match the expected pattern
+    ExpectedPatternMismatch(String),
+}
+
+impl Display for SheetNameParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExpectedPatternMismatch(val) => write!(f, "Sheet <{val}> does not match expected pattern `{SHEET_PATTERN_DESC}`"),
+        }
+    }
+}
+
+impl std::error::Error for SheetNameParseError {}
+
+impl SheetName {
+    /// Kind of sheet
+    pub fn kind(&self) -> SheetKind {
+        self.kind
+    }
+
+    /// Sheet number (without prefix or suffixes)
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Suffixes of the sheet name (without their separators)
+    pub fn suffixes(&self) -> &[String] {
+        &self.suffixes
+    }
+
+    /// Sheet name, as it is in Sigmanest
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Stock sheet that a remnant was cut from
+    ///
+    /// Remnants keep the number of the sheet they were cut from,
+    /// so `W12345-1` is a remnant of `S12345`.
+    /// Returns `None` if this is not a remnant.
+    pub fn source(&self) -> Option<SheetName> {
+        match self.kind {
+            SheetKind::Remnant => Some(Self {
+                kind: SheetKind::Stock,
+                number: self.number,
+                suffixes: Vec::new(),
+                name: format!("{}{:05}", SheetKind::Stock.prefix(), self.number)
+            }),
+            _ => None
+        }
+    }
+}
+
+impl FromStr for SheetName {
+    type Err = SheetNameParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        log::trace!("Parsing SheetName <FromStr> {value}");
+
+        let name = value.trim().to_uppercase();
+        match SHEET_PATTERN.captures(&name).map(|c| c.extract()) {
+            Some((_, [prefix, number, suffixes])) => {
+                let kind = match prefix {
+                    "S" => SheetKind::Stock,
+                    "W" => SheetKind::Remnant,
+                    _   => SheetKind::Other,
+                };
+
+                Ok(Self {
+                    kind,
+                    // unwrap is safe here because the regex will assure that parse() does not fail
+                    number: number.parse().unwrap(),
+                    suffixes: split_suffixes(suffixes),
+                    name
+                })
+            },
+            None => Err(SheetNameParseError::ExpectedPatternMismatch(value.into()))
+        }
+    }
+}
+
+impl Display for SheetName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Ord for SheetName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.number.cmp(&other.number)
+            .then_with(|| self.kind.cmp(&other.kind))
+            .then_with(|| cmp_suffixes(&self.suffixes, &other.suffixes))
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+impl PartialOrd for SheetName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stock_sheet() {
+        let sheet = "s12345".parse::<SheetName>().unwrap();
+
+        assert_eq!(sheet.kind(), SheetKind::Stock);
+        assert_eq!(sheet.number(), 12345);
+        assert_eq!(sheet.to_string(), "S12345");
+        assert_eq!(sheet.source(), None);
+    }
+
+    #[test]
+    fn test_remnant_source() {
+        let remnant = "W01234_2".parse::<SheetName>().unwrap();
+
+        assert_eq!(remnant.kind(), SheetKind::Remnant);
+        assert_eq!(remnant.suffixes(), ["2"]);
+        assert_eq!(remnant.source().unwrap().to_string(), "S01234");
+    }
+
+    #[test]
+    fn test_expected_pattern_mismatch() {
+        for name in ["A12345", "S1234", "S123456", "12345", "S12345-"] {
+            assert_eq!(
+                name.parse::<SheetName>(),
+                Err(SheetNameParseError::ExpectedPatternMismatch(String::from(name)))
+            );
+        }
+    }
+
+    #[test]
+    fn test_geometry() {
+        let sheet = Sheet {
+            size: Some(SheetSize { thickness: 0.5, width: 96.0, length: 240.0 }),
+            remaining_area: Some(5760.0),
+
+            ..Sheet::new("S12345".parse().unwrap())
+        };
+
+        assert_eq!(sheet.total_area(), Some(23040.0));
+        assert_eq!(sheet.nested_area(), Some(17280.0));
+        assert_eq!(sheet.utilization(17280.0), Some(0.75));
+        assert_eq!(sheet.exceeds_area(23040.5), Some(true));
+        assert_eq!(sheet.size.unwrap().to_string(), "0.500 x 96 x 240");
+
+        let unknown = Sheet::new("S12345".parse().unwrap());
+        assert_eq!(unknown.utilization(100.0), None);
+        assert_eq!(unknown.nested_area(), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut sheets: Vec<SheetName> = ["W12345-10", "S12345", "X00001", "W12345-2"]
+            .into_iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        sheets.sort();
+
+        assert_eq!(
+            sheets.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["X00001", "S12345", "W12345-2", "W12345-10"]
+        );
+    }
 }
@@ -0,0 +1,296 @@
+
+//! SAP material master
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use super::JobShipment;
+
+static PATTERN_DESC: &str = "`[9-][HPS]50[W][{T or F}{1-3}]-{4-digit thickness}` or `{job}{structure}{2-digit shipment}-{5-digit thickness}`";
+static STOCK_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^(9-)?(HPS)?50(W)?([TF][123])?-(\d{4})([[:alpha:]]*)$").expect("failed to build STOCK_PATTERN regex"));
+static PROJECT_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^(\d{7})([[:alpha:]])(\d{2})-(\d{5})([[:alpha:]]*)$").expect("failed to build PROJECT_PATTERN regex"));
+
+/// Steel grade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Grade {
+    /// Grade 50
+    A50,
+    /// Grade 50W (weathering)
+    A50W,
+    /// High performance steel, grade 50W
+    Hps50W,
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::A50    => write!(f, "50"),
+            Self::A50W   => write!(f, "50W"),
+            Self::Hps50W => write!(f, "HPS50W"),
+        }
+    }
+}
+
+/// Charpy impact test zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TestZone {
+    /// Non-fracture critical, zone 1
+    T1,
+    /// Non-fracture critical, zone 2
+    T2,
+    /// Non-fracture critical, zone 3
+    T3,
+    /// Fracture critical, zone 1
+    F1,
+    /// Fracture critical, zone 2
+    F2,
+    /// Fracture critical, zone 3
+    F3,
+}
+
+impl TestZone {
+    /// if the test zone is for fracture critical members
+    pub fn is_fracture_critical(&self) -> bool {
+        matches!(self, Self::F1 | Self::F2 | Self::F3)
+    }
+
+    /// zone number (1-3)
+    pub fn zone(&self) -> u8 {
+        match self {
+            Self::T1 | Self::F1 => 1,
+            Self::T2 | Self::F2 => 2,
+            Self::T3 | Self::F3 => 3,
+        }
+    }
+}
+
+impl Display for TestZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.is_fracture_critical() {
+            true  => write!(f, "F{}", self.zone()),
+            false => write!(f, "T{}", self.zone()),
+        }
+    }
+}
+
+/// SAP material master
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MaterialMaster {
+    /// Stock material (i.e. `50W-0008` or `9-HPS50WF2-0108`)
+    Stock {
+        /// material master has the `9-` prefix
+        prefixed: bool,
+        /// Steel grade
+        grade: Grade,
+        /// Charpy test zone
+        zone: Option<TestZone>,
+        /// Thickness code (4 digits)
+        thickness: u32,
+        /// any trailing letters
+        suffix: String,
+    },
+    /// Material purchased for a project (i.e. `1234567A01-05000`)
+    Project {
+        /// Job and shipment the material was purchased for
+        job: JobShipment,
+        /// Thickness code (5 digits)
+        thickness: u32,
+        /// any trailing letters
+        suffix: String,
+    }
+}
+
+/// Error parsing a [`MaterialMaster`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum MaterialMasterParseError {
+    /// `HPS` material that is not grade 50W
+    InvalidGrade(String),
+    /// Material master does not match either expected pattern
+    ExpectedPatternMismatch(String),
+}
+
+impl Display for MaterialMasterParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidGrade(val) => write!(f, "Material master <{val}> is HPS, but not grade 50W"),
+            Self::ExpectedPatternMismatch(val) => write!(f, "Material master <{val}> does not match either of the expected patterns {PATTERN_DESC}"),
+        }
+    }
+}
+
+impl std::error::Error for MaterialMasterParseError {}
+
+impl MaterialMaster {
+    /// if this is stock material
+    pub fn is_stock(&self) -> bool {
+        matches!(self, Self::Stock { .. })
+    }
+
+    /// if this is material purchased for a project
+    pub fn is_project(&self) -> bool {
+        matches!(self, Self::Project { .. })
+    }
+
+    /// Steel grade (stock material only)
+    pub fn grade(&self) -> Option<Grade> {
+        match self {
+            Self::Stock { grade, .. } => Some(*grade),
+            Self::Project { .. } => None
+        }
+    }
+
+    /// Charpy test zone (stock material only)
+    pub fn zone(&self) -> Option<TestZone> {
+        match self {
+            Self::Stock { zone, .. } => *zone,
+            Self::Project { .. } => None
+        }
+    }
+
+    /// Thickness code
+    pub fn thickness_code(&self) -> u32 {
+        match self {
+            Self::Stock { thickness, .. } | Self::Project { thickness, .. } => *thickness
+        }
+    }
+
+    /// Job and shipment the material was purchased for (project material only)
+    pub fn job_shipment(&self) -> Option<&JobShipment> {
+        match self {
+            Self::Stock { .. } => None,
+            Self::Project { job, .. } => Some(job)
+        }
+    }
+}
+
+impl FromStr for MaterialMaster {
+    type Err = MaterialMasterParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        log::trace!("Parsing MaterialMaster <FromStr> {value}");
+
+        let mm = value.trim().to_uppercase();
+
+        // unwraps are safe here because the regex will assure that parse() does not fail
+        if let Some(caps) = STOCK_PATTERN.captures(&mm) {
+            let grade = match (caps.get(2).is_some(), caps.get(3).is_some()) {
+                (false, false) => Grade::A50,
+                (false, true)  => Grade::A50W,
+                (true, true)   => Grade::Hps50W,
+                (true, false)  => return Err(MaterialMasterParseError::InvalidGrade(value.into()))
+            };
+
+            let zone = caps.get(4).map(|zone| match zone.as_str() {
+                "T1" => TestZone::T1,
+                "T2" => TestZone::T2,
+                "T3" => TestZone::T3,
+                "F1" => TestZone::F1,
+                "F2" => TestZone::F2,
+                _    => TestZone::F3,
+            });
+
+            return Ok(Self::Stock {
+                prefixed: caps.get(1).is_some(),
+                grade,
+                zone,
+                thickness: caps[5].parse().unwrap(),
+                suffix: caps[6].into()
+            });
+        }
+
+        if let Some(caps) = PROJECT_PATTERN.captures(&mm) {
+            let (_, [job, structure, shipment, thickness, suffix]) = caps.extract();
+
+            return Ok(Self::Project {
+                job: JobShipment::new(job.parse().unwrap(), structure.chars().nth(0).unwrap(), shipment.parse().unwrap()),
+                thickness: thickness.parse().unwrap(),
+                suffix: suffix.into()
+            });
+        }
+
+        Err(MaterialMasterParseError::ExpectedPatternMismatch(value.into()))
+    }
+}
+
+impl Display for MaterialMaster {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stock { prefixed, grade, zone, thickness, suffix } => {
+                if *prefixed {
+                    write!(f, "9-")?;
+                }
+
+                write!(f, "{grade}")?;
+                if let Some(zone) = zone {
+                    write!(f, "{zone}")?;
+                }
+
+                write!(f, "-{thickness:04}{suffix}")
+            },
+            Self::Project { job, thickness, suffix } =>
+                write!(f, "{}{}{:02}-{thickness:05}{suffix}", job.job(), job.structure(), job.shipment()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stock() {
+        assert_eq!(
+            "9-hps50wf2-0108".parse::<MaterialMaster>().unwrap(),
+            MaterialMaster::Stock {
+                prefixed: true,
+                grade: Grade::Hps50W,
+                zone: Some(TestZone::F2),
+                thickness: 108,
+                suffix: String::new()
+            }
+        );
+
+        let mm = "50-0008A".parse::<MaterialMaster>().unwrap();
+        assert!(mm.is_stock());
+        assert_eq!(mm.grade(), Some(Grade::A50));
+        assert_eq!(mm.zone(), None);
+        assert_eq!(mm.thickness_code(), 8);
+    }
+
+    #[test]
+    fn test_project() {
+        let mm = "1234567A01-05000".parse::<MaterialMaster>().unwrap();
+
+        assert!(mm.is_project());
+        assert_eq!(mm.grade(), None);
+        assert_eq!(mm.thickness_code(), 5000);
+        assert_eq!(mm.job_shipment(), Some(&"1234567A-1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for mm in ["50W-0008", "50T2-0100", "9-HPS50WF3-0250B", "1234567B12-01125", "1234567A01-05000X"] {
+            assert_eq!(mm.parse::<MaterialMaster>().unwrap().to_string(), mm);
+        }
+    }
+
+    #[test]
+    fn test_invalid_grade() {
+        assert_eq!(
+            "HPS50-0008".parse::<MaterialMaster>(),
+            Err(MaterialMasterParseError::InvalidGrade(String::from("HPS50-0008")))
+        );
+    }
+
+    #[test]
+    fn test_expected_pattern_mismatch() {
+        for mm in ["36-0008", "50W-008", "1234567A1-05000", "50W-0008-1"] {
+            assert_eq!(
+                mm.parse::<MaterialMaster>(),
+                Err(MaterialMasterParseError::ExpectedPatternMismatch(String::from(mm)))
+            );
+        }
+    }
+}
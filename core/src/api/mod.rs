@@ -1,10 +1,30 @@
 
 //! Common api
 
+mod hd;
+mod identifier;
+mod issue;
 mod jobshipment;
 mod matl;
+mod mm;
+mod part;
+mod plant;
+mod program;
+#[cfg(feature = "serde")]
+mod ser;
+#[cfg(feature = "tiberius")]
+mod sql;
+mod uom;
 mod wbs;
 
-pub use jobshipment::JobShipment;
-pub use matl::Sheet;
-pub use wbs::Wbs;
+pub use hd::HdTable;
+pub use identifier::{ClassifyError, Identifier, IdentifierKind};
+pub use issue::{CostCenterRules, Issue, IssueCode};
+pub use jobshipment::{JobShipment, JobShipmentParseError, ShipmentKind};
+pub use matl::{Sheet, SheetKind, SheetName, SheetNameParseError, SheetSize};
+pub use mm::{Grade, MaterialMaster, MaterialMasterParseError, TestZone};
+pub use part::{Part, PartParseError};
+pub use plant::{Plant, PlantMap, PlantParseError};
+pub use program::{ProgramName, ProgramNameParseError};
+pub use uom::{Quantity, STEEL_DENSITY, UnitOfMeasure, UnitOfMeasureParseError, UomConversionError};
+pub use wbs::{HdLookup, Wbs, WbsConversionError, WbsParseError};
@@ -0,0 +1,164 @@
+
+//! Part mark
+
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+static PATTERN_DESC: &str = "{7-digit number}{single letter}-{piece mark}";
+static PART_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^(\d{7})([[:alpha:]]?)-([[:alnum:]]+(?:-[[:alnum:]]+)*)$").expect("failed to build PART_PATTERN regex"));
+
+/// Part mark (job number, structure letter and piece mark)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Part {
+    /// Job Number
+    job: u32,
+    /// Structure letter
+    structure: char,
+    /// Piece mark
+    mark: String
+}
+
+/// Error parsing a [`Part`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum PartParseError {
+    /// Part is missing the structure letter after the job number
+    MissingStructureLetter(String),
+    /// Part does not match the expected pattern
+    ExpectedPatternMismatch(String),
+}
+
+impl Display for PartParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingStructureLetter(val) => write!(f, "Part <{val}> is missing the structure letter"),
+            Self::ExpectedPatternMismatch(val) => write!(f, "Part <{val}> does not match expected pattern `{PATTERN_DESC}`"),
+        }
+    }
+}
+
+impl std::error::Error for PartParseError {}
+
+impl Part {
+    /// Normalizes a part name the same way Sigmanest part names are matched to SAP
+    ///
+    /// Sigmanest does not allow `-` in some part names, so `_` is used instead
+    /// (i.e. `1234567A_X1A` is the part `1234567A-X1A`)
+    pub fn normalize(value: &str) -> Cow<'_, str> {
+        match value.contains('_') {
+            true  => Cow::Owned(value.trim().replace('_', "-")),
+            false => Cow::Borrowed(value.trim())
+        }
+    }
+
+    /// Job number
+    pub fn job(&self) -> u32 {
+        self.job
+    }
+
+    /// Structure letter
+    pub fn structure(&self) -> char {
+        self.structure
+    }
+
+    /// Piece mark (without the job and structure)
+    pub fn mark(&self) -> &str {
+        &self.mark
+    }
+}
+
+impl FromStr for Part {
+    type Err = PartParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        log::trace!("Parsing Part <FromStr> {value}");
+
+        let normalized = Self::normalize(value);
+        match PART_PATTERN.captures(&normalized).map(|c| c.extract()) {
+            Some((_, [_, "", _])) => Err(PartParseError::MissingStructureLetter(value.into())),
+            Some((_, [job, structure, mark])) => {
+                // unwrap is safe here because the regex will assure that parse() does not fail
+                let job = job.parse().unwrap();
+                let structure = structure.to_uppercase().chars().nth(0).unwrap();
+                let mark = mark.to_uppercase();
+
+                Ok ( Self { job, structure, mark })
+            },
+            _ => Err(PartParseError::ExpectedPatternMismatch(value.into()))
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}-{}", self.job, self.structure, self.mark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_input() {
+        assert_eq!(
+            "1234567a-x1a".parse::<Part>().unwrap(),
+            Part {
+                job: 1234567,
+                structure: 'A',
+                mark: String::from("X1A")
+            }
+        );
+    }
+
+    #[test]
+    fn test_multiple_segments() {
+        let part = "1234567B-G12-2".parse::<Part>().unwrap();
+
+        assert_eq!(part.job(), 1234567);
+        assert_eq!(part.structure(), 'B');
+        assert_eq!(part.mark(), "G12-2");
+        assert_eq!(part.to_string(), "1234567B-G12-2");
+    }
+
+    #[test]
+    fn test_underscore_normalization() {
+        assert_eq!(Part::normalize("1234567A_X1A_1"), "1234567A-X1A-1");
+        assert_eq!(
+            "1234567A_X1A".parse::<Part>().unwrap(),
+            "1234567A-X1A".parse::<Part>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_missing_structure_letter() {
+        assert_eq!(
+            "1234567-X1A".parse::<Part>(),
+            Err(PartParseError::MissingStructureLetter(String::from("1234567-X1A")))
+        );
+    }
+
+    #[test]
+    fn test_expected_pattern_mismatch() {
+        assert_eq!(
+            "52198-1".parse::<Part>(),
+            Err(PartParseError::ExpectedPatternMismatch(String::from("52198-1")))
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut parts: Vec<Part> = ["1234567B-X1A", "1234567A-X2A", "1234560A-X9A", "1234567A-X1A"]
+            .into_iter()
+            .map(|p| p.parse().unwrap())
+            .collect();
+        parts.sort();
+
+        assert_eq!(
+            parts.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["1234560A-X9A", "1234567A-X1A", "1234567A-X2A", "1234567B-X1A"]
+        );
+    }
+}
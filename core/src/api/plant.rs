@@ -0,0 +1,185 @@
+
+//! SAP plants and the machines in them
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use crate::config::{Validate, Validation};
+
+static PATTERN_DESC: &str = "{4 letters or digits} (i.e. `HS01`)";
+static PLANT_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[[:alnum:]]{4}$").expect("failed to build PLANT_PATTERN regex"));
+
+/// SAP plant (i.e. `HS01`)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Plant(String);
+
+/// Error parsing a [`Plant`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum PlantParseError {
+    /// Plant does not match the expected pattern
+    ExpectedPatternMismatch(String),
+}
+
+impl Display for PlantParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExpectedPatternMismatch(val) => write!(f, "Plant <{val}> does not match expected pattern `{PATTERN_DESC}`"),
+        }
+    }
+}
+
+impl std::error::Error for PlantParseError {}
+
+impl Plant {
+    /// Plant code, as it is in SAP
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Plant {
+    type Err = PlantParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        log::trace!("Parsing Plant <FromStr> {value}");
+
+        let plant = value.trim().to_uppercase();
+        match PLANT_PATTERN.is_match(&plant) {
+            true  => Ok(Self(plant)),
+            false => Err(PlantParseError::ExpectedPatternMismatch(value.into()))
+        }
+    }
+}
+
+impl Display for Plant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Mapping of machine names to the [`Plant`] the machine is in
+///
+/// Machines are matched, in order, against a regex pattern.
+/// The first pattern that matches determines the plant.
+/// ```toml
+/// [[machine]]
+/// pattern = "^Plant_3"
+/// plant = "HS02"
+/// ```
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(try_from = "PlantMapFile", into = "PlantMapFile")]
+pub struct PlantMap {
+    machines: Vec<(Regex, Plant)>
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct PlantMapFile {
+    #[serde(default)]
+    machine: Vec<PlantMapLine>
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct PlantMapLine {
+    pattern: String,
+    plant: String
+}
+
+impl PlantMap {
+    /// add a machine name pattern, after any existing patterns
+    pub fn push(&mut self, pattern: &str, plant: Plant) -> anyhow::Result<()> {
+        self.machines.push((Regex::new(pattern)?, plant));
+
+        Ok(())
+    }
+
+    /// plant that a machine is in, if the machine matches any pattern
+    pub fn plant(&self, machine: &str) -> Option<&Plant> {
+        self.machines.iter()
+            .find(|(pattern, _)| pattern.is_match(machine))
+            .map(|(_, plant)| plant)
+    }
+
+    /// number of machine name patterns
+    pub fn len(&self) -> usize {
+        self.machines.len()
+    }
+
+    /// if there are no machine name patterns
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty()
+    }
+}
+
+impl Validate for PlantMap {
+    fn validate_into(&self, report: &mut Validation) {
+        for (i, (pattern, _)) in self.machines.iter().enumerate() {
+            report.not_placeholder(&format!("machine[{i}].pattern"), pattern.as_str());
+        }
+    }
+}
+
+impl TryFrom<PlantMapFile> for PlantMap {
+    type Error = anyhow::Error;
+
+    fn try_from(value: PlantMapFile) -> Result<Self, Self::Error> {
+        let mut map = Self::default();
+        for line in value.machine {
+            map.push(&line.pattern, line.plant.parse()?)
+                .map_err(|e| anyhow::anyhow!("invalid machine pattern `{}`: {e}", line.pattern))?;
+        }
+
+        Ok(map)
+    }
+}
+
+impl From<PlantMap> for PlantMapFile {
+    fn from(value: PlantMap) -> Self {
+        Self {
+            machine: value.machines.into_iter()
+                .map(|(pattern, plant)| PlantMapLine { pattern: pattern.to_string(), plant: plant.to_string() })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plant() {
+        assert_eq!("hs01".parse::<Plant>().unwrap().as_str(), "HS01");
+        assert_eq!(
+            "HS1".parse::<Plant>(),
+            Err(PlantParseError::ExpectedPatternMismatch(String::from("HS1")))
+        );
+    }
+
+    #[test]
+    fn test_plant_map() {
+        let map: PlantMap = toml::from_str(r#"
+            [[machine]]
+            pattern = "^Plant_3"
+            plant = "HS02"
+
+            [[machine]]
+            pattern = "^Plant_[12]"
+            plant = "HS01"
+        "#).unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.plant("Plant_3_Gemini").map(Plant::as_str), Some("HS02"));
+        assert_eq!(map.plant("Plant_1_Titan").map(Plant::as_str), Some("HS01"));
+        assert_eq!(map.plant("Farley"), None);
+    }
+
+    #[test]
+    fn test_invalid_plant_map() {
+        assert!(toml::from_str::<PlantMap>("[[machine]]\npattern = \"(\"\nplant = \"HS01\"").is_err());
+        assert!(toml::from_str::<PlantMap>("[[machine]]\npattern = \"^Plant_1\"\nplant = \"HS001\"").is_err());
+    }
+}
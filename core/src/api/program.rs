@@ -0,0 +1,168 @@
+
+//! Sigmanest program name
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+static PATTERN_DESC: &str = "{5 to 9 digit number}[{- or _}{suffix}...]";
+static PROGRAM_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"^(\d{5,9})((?:[\-_][[:alnum:]]+)*)$").expect("failed to build PROGRAM_PATTERN regex"));
+
+/// Sigmanest program name (i.e. `52198` or `52198-2A`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProgramName {
+    /// Numeric program base
+    base: u32,
+    /// Suffixes (after a `-` or `_`)
+    suffixes: Vec<String>,
+    /// Program name, as given
+    name: String
+}
+
+/// Error parsing a [`ProgramName`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum ProgramNameParseError {
+    /// Program name does not match the expected pattern
+    ExpectedPatternMismatch(String),
+}
+
+impl Display for ProgramNameParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExpectedPatternMismatch(val) => write!(f, "Program <{val}> does not match expected pattern `{PATTERN_DESC}`"),
+        }
+    }
+}
+
+impl std::error::Error for ProgramNameParseError {}
+
+impl ProgramName {
+    /// Numeric base of the program name (everything before the first suffix)
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// Suffixes of the program name (without their separators)
+    pub fn suffixes(&self) -> &[String] {
+        &self.suffixes
+    }
+
+    /// Program name, as it is in Sigmanest
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+impl FromStr for ProgramName {
+    type Err = ProgramNameParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        log::trace!("Parsing ProgramName <FromStr> {value}");
+
+        let name = value.trim();
+        match PROGRAM_PATTERN.captures(name).map(|c| c.extract()) {
+            Some((_, [base, suffixes])) => Ok(Self {
+                // unwrap is safe here because the regex will assure that parse() does not fail
+                base: base.parse().unwrap(),
+                suffixes: split_suffixes(suffixes),
+                name: name.to_uppercase()
+            }),
+            None => Err(ProgramNameParseError::ExpectedPatternMismatch(value.into()))
+        }
+    }
+}
+
+impl Display for ProgramName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Ord for ProgramName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.base.cmp(&other.base)
+            .then_with(|| cmp_suffixes(&self.suffixes, &other.suffixes))
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+impl PartialOrd for ProgramName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// splits a `-` or `_` delimited suffix string into its (uppercase) parts
+pub(super) fn split_suffixes(suffixes: &str) -> Vec<String> {
+    suffixes
+        .split(['-', '_'])
+        .filter(|s| !s.is_empty())
+        .map(str::to_uppercase)
+        .collect()
+}
+
+/// compares suffixes numerically, if both are numbers, else lexically
+///
+/// this keeps `52198-2` before `52198-10`
+pub(super) fn cmp_suffixes(left: &[String], right: &[String]) -> Ordering {
+    for (l, r) in left.iter().zip(right) {
+        let ordering = match (l.parse::<u32>(), r.parse::<u32>()) {
+            (Ok(l), Ok(r)) => l.cmp(&r),
+            _ => l.cmp(r)
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    left.len().cmp(&right.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_input() {
+        let program = "52198_2a".parse::<ProgramName>().unwrap();
+
+        assert_eq!(program.base(), 52198);
+        assert_eq!(program.suffixes(), ["2A"]);
+        assert_eq!(program.to_string(), "52198_2A");
+    }
+
+    #[test]
+    fn test_no_suffix() {
+        let program = "52198".parse::<ProgramName>().unwrap();
+
+        assert_eq!(program.base(), 52198);
+        assert!(program.suffixes().is_empty());
+    }
+
+    #[test]
+    fn test_expected_pattern_mismatch() {
+        for name in ["5219", "52198-", "S52198", "1234567A-1", "52198 2"] {
+            assert_eq!(
+                name.parse::<ProgramName>(),
+                Err(ProgramNameParseError::ExpectedPatternMismatch(String::from(name)))
+            );
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut programs: Vec<ProgramName> = ["52198-10", "52198", "52198-2", "100000", "52198-2-A"]
+            .into_iter()
+            .map(|p| p.parse().unwrap())
+            .collect();
+        programs.sort();
+
+        assert_eq!(
+            programs.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["52198", "52198-2", "52198-2-A", "52198-10", "100000"]
+        );
+    }
+}
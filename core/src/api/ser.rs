@@ -0,0 +1,64 @@
+
+//! Serde support, using the canonical string form of each type
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{JobShipment, MaterialMaster, Part, Plant, ProgramName, SheetName, UnitOfMeasure, Wbs};
+
+/// (de)serialize a type as its [`Display`](std::fmt::Display) / [`FromStr`](std::str::FromStr) string
+macro_rules! serde_as_str {
+    ($($ty:ty),+) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    String::deserialize(deserializer)?
+                        .parse()
+                        .map_err(serde::de::Error::custom)
+                }
+            }
+        )+
+    };
+}
+
+serde_as_str!(JobShipment, MaterialMaster, Part, Plant, ProgramName, SheetName, UnitOfMeasure, Wbs);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Sheet;
+
+    #[test]
+    fn test_canonical_strings() {
+        assert_eq!(serde_json::to_string(&Wbs::Hd { project: 1234567, id: 123 }).unwrap(), r#""D-1234567-00123""#);
+        assert_eq!(serde_json::to_string(&JobShipment::new(1234567, 'a', 1)).unwrap(), r#""1234567A-1""#);
+
+        assert_eq!(serde_json::from_str::<Wbs>(r#""S-1234567-2-01""#).unwrap(), Wbs::Legacy { project: 1234567, shipment: 1 });
+        assert_eq!(serde_json::from_str::<ProgramName>(r#""52198-2a""#).unwrap().as_str(), "52198-2A");
+    }
+
+    #[test]
+    fn test_invalid_string() {
+        let err = serde_json::from_str::<Wbs>(r#""D-123-00123""#).unwrap_err();
+        assert!(err.to_string().contains("D-123-00123"));
+    }
+
+    #[test]
+    fn test_sheet() {
+        let json = r#"{"name":"W12345-1","mm":"50W-0008","heat":"8A1234","po":"4500123456","wbs":"D-1234567-00123","size":{"thickness":0.5,"width":96.0,"length":120.0},"remaining_area":null}"#;
+        let sheet: Sheet = serde_json::from_str(json).unwrap();
+
+        assert_eq!(sheet.name.to_string(), "W12345-1");
+        assert_eq!(sheet.wbs, Some(Wbs::Hd { project: 1234567, id: 123 }));
+        assert_eq!(serde_json::to_string(&sheet).unwrap(), json);
+
+        // dimensions are optional
+        let sheet: Sheet = serde_json::from_str(r#"{"name":"S12345","mm":null,"heat":"","po":"","wbs":null}"#).unwrap();
+        assert_eq!(sheet.size, None);
+    }
+}
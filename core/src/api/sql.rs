@@ -0,0 +1,64 @@
+
+//! SQL Server (tiberius) support, using the canonical string form of each type
+
+use std::borrow::Cow;
+
+use tiberius::{ColumnData, FromSql, ToSql};
+
+use super::{JobShipment, MaterialMaster, Part, Plant, ProgramName, SheetName, UnitOfMeasure, Wbs};
+
+/// bind and read a type as its [`Display`](std::fmt::Display) / [`FromStr`](std::str::FromStr) string
+///
+/// Values that fail to parse are returned as a [`tiberius::error::Error::Conversion`]
+macro_rules! sql_as_str {
+    ($($ty:ty),+) => {
+        $(
+            impl ToSql for $ty {
+                fn to_sql(&self) -> ColumnData<'_> {
+                    ColumnData::String(Some(Cow::Owned(self.to_string())))
+                }
+            }
+
+            impl<'a> FromSql<'a> for $ty {
+                fn from_sql(value: &'a ColumnData<'static>) -> tiberius::Result<Option<Self>> {
+                    <&str as FromSql>::from_sql(value)?
+                        .map(|s| s.parse()
+                            .map_err(|e| tiberius::error::Error::Conversion(format!("{e}").into()))
+                        )
+                        .transpose()
+                }
+            }
+        )+
+    };
+}
+
+sql_as_str!(JobShipment, MaterialMaster, Part, Plant, ProgramName, SheetName, UnitOfMeasure, Wbs);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_sql() {
+        let wbs = Wbs::Legacy { project: 1234567, shipment: 1 };
+
+        assert_eq!(wbs.to_sql(), ColumnData::String(Some(Cow::from("S-1234567-2-01"))));
+    }
+
+    #[test]
+    fn test_from_sql() {
+        let program = ColumnData::String(Some(Cow::from("52198-2a")));
+        assert_eq!(ProgramName::from_sql(&program).unwrap().unwrap().as_str(), "52198-2A");
+
+        assert_eq!(JobShipment::from_sql(&ColumnData::String(None)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        let invalid = ColumnData::String(Some(Cow::from("D-123-00123")));
+        assert!(matches!(Wbs::from_sql(&invalid), Err(tiberius::error::Error::Conversion(_))));
+
+        // not a string column
+        assert!(SheetName::from_sql(&ColumnData::I32(Some(12345))).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+
+//! Units of measure and quantities
+
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Div, Mul};
+use std::str::FromStr;
+
+/// Density of steel, in pounds per cubic inch
+pub const STEEL_DENSITY: f64 = 0.2836;
+
+const SQ_IN_PER_SQ_FT: f64 = 144.0;
+
+/// SAP unit of measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitOfMeasure {
+    /// Each (`EA`)
+    Each,
+    /// Square inches (`IN2`)
+    SquareInch,
+    /// Square feet (`FT2`)
+    SquareFoot,
+    /// Pounds (`LB`)
+    Pound,
+}
+
+/// Error parsing a [`UnitOfMeasure`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum UnitOfMeasureParseError {
+    /// Unit of measure is not one of `EA`, `IN2`, `FT2` or `LB`
+    Unknown(String),
+}
+
+impl Display for UnitOfMeasureParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(val) => write!(f, "Unit of measure <{val}> is not one of `EA`, `IN2`, `FT2` or `LB`"),
+        }
+    }
+}
+
+impl std::error::Error for UnitOfMeasureParseError {}
+
+/// Error converting a [`Quantity`] to another [`UnitOfMeasure`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum UomConversionError {
+    /// Units cannot be converted between (i.e. `EA` to `LB`)
+    Incompatible(UnitOfMeasure, UnitOfMeasure),
+    /// Converting between area and weight needs the plate thickness
+    MissingThickness(UnitOfMeasure, UnitOfMeasure),
+}
+
+impl Display for UomConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Incompatible(from, to) => write!(f, "Cannot convert {from} to {to}"),
+            Self::MissingThickness(from, to) => write!(f, "Cannot convert {from} to {to} without a plate thickness"),
+        }
+    }
+}
+
+impl std::error::Error for UomConversionError {}
+
+impl UnitOfMeasure {
+    /// if this is a unit of area
+    pub fn is_area(&self) -> bool {
+        matches!(self, Self::SquareInch | Self::SquareFoot)
+    }
+
+    /// if this is a unit of weight
+    pub fn is_weight(&self) -> bool {
+        matches!(self, Self::Pound)
+    }
+}
+
+impl FromStr for UnitOfMeasure {
+    type Err = UnitOfMeasureParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        log::trace!("Parsing UnitOfMeasure <FromStr> {value}");
+
+        match value.trim().to_uppercase().as_str() {
+            "EA"  => Ok(Self::Each),
+            "IN2" => Ok(Self::SquareInch),
+            "FT2" => Ok(Self::SquareFoot),
+            "LB"  => Ok(Self::Pound),
+            _ => Err(UnitOfMeasureParseError::Unknown(value.into()))
+        }
+    }
+}
+
+impl Display for UnitOfMeasure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Each       => write!(f, "EA"),
+            Self::SquareInch => write!(f, "IN2"),
+            Self::SquareFoot => write!(f, "FT2"),
+            Self::Pound      => write!(f, "LB"),
+        }
+    }
+}
+
+/// Quantity in a [`UnitOfMeasure`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    value: f64,
+    uom: UnitOfMeasure,
+}
+
+impl Quantity {
+    /// quantity of `value` in `uom`
+    pub fn new(value: f64, uom: UnitOfMeasure) -> Self {
+        Self { value, uom }
+    }
+
+    /// Amount, in [`Quantity::uom`]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Unit of measure
+    pub fn uom(&self) -> UnitOfMeasure {
+        self.uom
+    }
+
+    /// Converts to another unit of measure
+    ///
+    /// `thickness` is the plate thickness (in inches), which is needed to convert between area and weight.
+    pub fn convert(&self, to: UnitOfMeasure, thickness: Option<f64>) -> Result<Self, UomConversionError> {
+        use UnitOfMeasure::*;
+
+        let value = match (self.uom, to) {
+            (from, to) if from == to => self.value,
+
+            (SquareInch, SquareFoot) => self.value / SQ_IN_PER_SQ_FT,
+            (SquareFoot, SquareInch) => self.value * SQ_IN_PER_SQ_FT,
+
+            (from, to) if from.is_area() && to.is_weight() => {
+                let thickness = thickness.ok_or(UomConversionError::MissingThickness(from, to))?;
+
+                self.in_square_inches() * thickness * STEEL_DENSITY
+            },
+            (from, to) if from.is_weight() && to.is_area() => {
+                let thickness = thickness.ok_or(UomConversionError::MissingThickness(from, to))?;
+
+                Self::new(self.value / (thickness * STEEL_DENSITY), SquareInch)
+                    .convert(to, None)?
+                    .value
+            },
+
+            (from, to) => return Err(UomConversionError::Incompatible(from, to))
+        };
+
+        Ok(Self::new(value, to))
+    }
+
+    /// Adds two quantities, converting `other` to this quantity's unit of measure
+    pub fn checked_add(self, other: Self) -> Result<Self, UomConversionError> {
+        Ok(Self::new(self.value + other.convert(self.uom, None)?.value, self.uom))
+    }
+
+    fn in_square_inches(&self) -> f64 {
+        match self.uom {
+            UnitOfMeasure::SquareFoot => self.value * SQ_IN_PER_SQ_FT,
+            _ => self.value
+        }
+    }
+}
+
+impl Mul<f64> for Quantity {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.value * rhs, self.uom)
+    }
+}
+
+impl Div<f64> for Quantity {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Self::new(self.value / rhs, self.uom)
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} {}", precision, self.value, self.uom),
+            None => write!(f, "{} {}", self.value, self.uom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use UnitOfMeasure::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {expected}, got {actual}");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("in2".parse::<UnitOfMeasure>().unwrap(), SquareInch);
+        assert_eq!(FromStr::from_str("LB"), Ok(Pound));
+        assert_eq!(
+            "KG".parse::<UnitOfMeasure>(),
+            Err(UnitOfMeasureParseError::Unknown(String::from("KG")))
+        );
+    }
+
+    #[test]
+    fn test_area() {
+        let area = Quantity::new(288.0, SquareInch);
+
+        assert_close(area.convert(SquareFoot, None).unwrap().value(), 2.0);
+        assert_close(area.convert(SquareFoot, None).unwrap().convert(SquareInch, None).unwrap().value(), 288.0);
+    }
+
+    #[test]
+    fn test_weight() {
+        let area = Quantity::new(2.0, SquareFoot);
+        let weight = area.convert(Pound, Some(0.5)).unwrap();
+
+        assert_eq!(weight.uom(), Pound);
+        assert_close(weight.value(), 288.0 * 0.5 * STEEL_DENSITY);
+        assert_close(weight.convert(SquareFoot, Some(0.5)).unwrap().value(), 2.0);
+
+        assert_eq!(area.convert(Pound, None), Err(UomConversionError::MissingThickness(SquareFoot, Pound)));
+    }
+
+    #[test]
+    fn test_incompatible() {
+        assert_eq!(
+            Quantity::new(1.0, Each).convert(SquareInch, Some(1.0)),
+            Err(UomConversionError::Incompatible(Each, SquareInch))
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let total = Quantity::new(144.0, SquareInch)
+            .checked_add(Quantity::new(1.0, SquareFoot))
+            .unwrap();
+
+        assert_eq!(total, Quantity::new(288.0, SquareInch));
+        assert_eq!(total * 2.0 / 4.0, Quantity::new(144.0, SquareInch));
+        assert!(total.checked_add(Quantity::new(1.0, Each)).is_err());
+        assert_eq!(format!("{total:.3}"), "288.000 IN2");
+    }
+}
@@ -3,15 +3,17 @@
 
 use regex::Regex;
 use std::fmt;
+use std::str::FromStr;
 use std::sync::LazyLock;
 
-// HD wbs element
-static HD_WBS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"D-(\d{7})-(\d{5})").unwrap());
-// old, non-hd, wbs element
-static LEGACY_WBS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"S-(\d{7})-2-(\d{2})").unwrap());
+use super::JobShipment;
+
+static PATTERN_DESC: &str = "`D-#######-#####` or `S-#######-2-##`";
+// HD or old, non-hd, wbs element somewhere in a string (for lenient parsing)
+static WBS_IN_TEXT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:D-\d{7}-\d{5}|S-\d{7}-2-\d{2})\b").unwrap());
 
 /// SAP Wbs element for cost association
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Wbs {
     /// Hard Dollar WBS element
     Hd {
@@ -29,33 +31,316 @@ pub enum Wbs {
     }
 }
 
-impl TryFrom<&str> for Wbs {
-    type Error = String;
+/// Error converting between a [`Wbs`] and a [`JobShipment`]
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum WbsConversionError {
+    /// Hard Dollar WBS element is not in the lookup
+    UnmappedHardDollar(Wbs),
+    /// Shipment does not fit in a legacy WBS element (2 digits)
+    ShipmentOutOfRange(JobShipment),
+    /// Legacy WBS elements are only for the first structure (`A`)
+    UnsupportedStructure(JobShipment),
+    /// Legacy WBS element where a Hard Dollar WBS element is needed
+    NotHardDollar(Wbs),
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        if let Some(caps) = HD_WBS.captures(value) {
-            let caps: [&str; 2] = caps.extract().1;
+impl fmt::Display for WbsConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnmappedHardDollar(wbs) => write!(f, "Hard Dollar WBS element `{wbs}` does not have a known job and shipment"),
+            Self::ShipmentOutOfRange(js) => write!(f, "Job-Shipment `{js}` shipment does not fit in a legacy WBS element"),
+            Self::UnsupportedStructure(js) => write!(f, "Job-Shipment `{js}` is not for structure `A`, which is the only structure of a legacy WBS element"),
+            Self::NotHardDollar(wbs) => write!(f, "WBS element `{wbs}` is not a Hard Dollar WBS element"),
+        }
+    }
+}
+
+impl std::error::Error for WbsConversionError {}
+
+impl Wbs {
+    /// Job and shipment of the WBS element, using `lookup` for Hard Dollar elements
+    pub fn job_shipment(&self, lookup: &impl HdLookup) -> Result<JobShipment, WbsConversionError> {
+        match self {
+            Self::Hd { project, id } => lookup.lookup(*project, *id)
+                .ok_or_else(|| WbsConversionError::UnmappedHardDollar(self.clone())),
+            Self::Legacy { .. } => JobShipment::try_from(self)
+        }
+    }
+}
+
+/// Lookup of Hard Dollar WBS line IDs to the job and shipment they are for
+pub trait HdLookup {
+    /// job and shipment for a Hard Dollar project and line ID, if it is known
+    fn lookup(&self, project: u32, id: u32) -> Option<JobShipment>;
+}
+
+/// Legacy WBS elements do not have a structure letter,
+/// so only the first structure (`A`) can be converted (see [`JobShipment::try_from`]).
+impl TryFrom<&JobShipment> for Wbs {
+    type Error = WbsConversionError;
+
+    fn try_from(value: &JobShipment) -> Result<Self, Self::Error> {
+        if value.structure() != 'A' {
+            return Err(WbsConversionError::UnsupportedStructure(value.clone()));
+        }
+
+        match value.shipment() {
+            shipment @ 0..=99 => Ok(Self::Legacy { project: value.job(), shipment }),
+            _ => Err(WbsConversionError::ShipmentOutOfRange(value.clone()))
+        }
+    }
+}
+
+/// Legacy WBS elements do not have a structure letter,
+/// so the first structure (`A`) is assumed.
+///
+/// Hard Dollar WBS elements need an [`HdLookup`] (see [`Wbs::job_shipment`]).
+impl TryFrom<&Wbs> for JobShipment {
+    type Error = WbsConversionError;
+
+    fn try_from(value: &Wbs) -> Result<Self, Self::Error> {
+        match value {
+            Wbs::Hd { .. } => Err(WbsConversionError::UnmappedHardDollar(value.clone())),
+            Wbs::Legacy { project, shipment } => Ok(JobShipment::new(*project, 'A', *shipment))
+        }
+    }
+}
+
+/// Error parsing a [`Wbs`]
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum WbsParseError {
+    /// Value is empty (or only whitespace)
+    Empty,
+    /// Value has surrounding whitespace or lowercase letters (only accepted by lenient parsing)
+    NotNormalized(String),
+    /// Value does not start with `D-` or `S-`
+    UnknownPrefix(String),
+    /// Project number is not 7 digits
+    InvalidProject(String),
+    /// Hard Dollar line ID is not 5 digits
+    InvalidId(String),
+    /// Legacy WBS element shipment is not 2 digits
+    InvalidShipment(String),
+    /// Value has the wrong number of segments, or a legacy WBS element is missing the `-2-`
+    ExpectedPatternMismatch(String),
+    /// No WBS element was found in the text (lenient parsing)
+    NotFound(String),
+}
+
+impl fmt::Display for WbsParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "WBS element is empty"),
+            Self::NotNormalized(val) => write!(f, "WBS element `{val}` has surrounding whitespace or lowercase letters"),
+            Self::UnknownPrefix(val) => write!(f, "WBS element `{val}` does not start with `D-` or `S-`"),
+            Self::InvalidProject(val) => write!(f, "WBS element `{val}` project is not a 7-digit number"),
+            Self::InvalidId(val) => write!(f, "WBS element `{val}` Hard Dollar line ID is not a 5-digit number"),
+            Self::InvalidShipment(val) => write!(f, "WBS element `{val}` shipment is not a 2-digit number"),
+            Self::ExpectedPatternMismatch(val) => write!(f, "WBS element `{val}` does not match either of the expected patterns {PATTERN_DESC}"),
+            Self::NotFound(val) => write!(f, "No WBS element found in `{val}`"),
+        }
+    }
+}
+
+impl std::error::Error for WbsParseError {}
+
+impl Wbs {
+    /// Parses a WBS element that must be exactly in its canonical form
+    /// (i.e. `D-1234567-00123` or `S-1234567-2-01`)
+    pub fn parse_strict(value: &str) -> Result<Self, WbsParseError> {
+        log::trace!("Parsing Wbs <strict> {value}");
+
+        if value.trim().is_empty() {
+            return Err(WbsParseError::Empty);
+        }
+
+        if value.trim() != value || value.to_uppercase() != value {
+            return Err(WbsParseError::NotNormalized(value.into()));
+        }
+
+        let is_digits = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_digit());
+        let err = |e: fn(String) -> WbsParseError| e(value.into());
+
+        // unwraps are safe here because `is_digits` assures that parse() does not fail
+        match value.split('-').collect::<Vec<_>>().as_slice() {
+            ["D", project, id] => {
+                if !is_digits(project, 7) { return Err(err(WbsParseError::InvalidProject)); }
+                if !is_digits(id, 5)      { return Err(err(WbsParseError::InvalidId)); }
+
+                Ok(Self::Hd { project: project.parse().unwrap(), id: id.parse().unwrap() })
+            },
+            ["S", project, "2", shipment] => {
+                if !is_digits(project, 7)  { return Err(err(WbsParseError::InvalidProject)); }
+                if !is_digits(shipment, 2) { return Err(err(WbsParseError::InvalidShipment)); }
 
-            // unwrap is safe here because the regex will assure that parse() does not fail
-            return Ok(Self::Hd { project: caps[0].parse().unwrap(), id: caps[1].parse().unwrap() });
+                Ok(Self::Legacy { project: project.parse().unwrap(), shipment: shipment.parse().unwrap() })
+            },
+            ["D" | "S", ..] => Err(err(WbsParseError::ExpectedPatternMismatch)),
+            _ => Err(err(WbsParseError::UnknownPrefix))
         }
+    }
+
+    /// Parses a WBS element, ignoring surrounding whitespace and case,
+    /// and pulling the WBS element out of any surrounding text
+    /// (i.e. ` d-1234567-00123 ` or `Mill: D-1234567-00123`)
+    pub fn parse_lenient(value: &str) -> Result<Self, WbsParseError> {
+        log::trace!("Parsing Wbs <lenient> {value}");
+
+        let normalized = value.trim().to_uppercase();
+        if normalized.is_empty() {
+            return Err(WbsParseError::Empty);
+        }
+
+        match WBS_IN_TEXT.find(&normalized) {
+            Some(found) => Self::parse_strict(found.as_str()),
+            None => match Self::parse_strict(&normalized) {
+                // report why the value, as a whole, is not a WBS element
+                Err(WbsParseError::UnknownPrefix(_)) => Err(WbsParseError::NotFound(value.into())),
+                Err(e) => Err(e),
+                Ok(wbs) => Ok(wbs)
+            }
+        }
+    }
+}
+
+impl FromStr for Wbs {
+    type Err = WbsParseError;
 
-        if let Some(caps) = LEGACY_WBS.captures(value) {
-            let caps: [&str; 2] = caps.extract().1;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::parse_strict(value)
+    }
+}
 
-            // unwrap is safe here because the regex will assure that parse() does not fail
-            return Ok(Self::Legacy { project: caps[0].parse().unwrap(), shipment: caps[1].parse().unwrap() });
-        } 
+impl TryFrom<&str> for Wbs {
+    type Error = WbsParseError;
 
-        Err(format!("WBS element `{value}` does not match either of the expected patterns `D-#######-#####` or `S-#######-2-##`"))
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse_strict(value)
     }
 }
 
 impl fmt::Display for Wbs {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Wbs::Hd { project, id } => write!(f, "D-{project}-{id}"),
-            Wbs::Legacy { project, shipment } => write!(f, "S-{project}-2-{shipment:02}"),
+            Wbs::Hd { project, id } => write!(f, "D-{project:07}-{id:05}"),
+            Wbs::Legacy { project, shipment } => write!(f, "S-{project:07}-2-{shipment:02}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct OneLine;
+
+    impl HdLookup for OneLine {
+        fn lookup(&self, project: u32, id: u32) -> Option<JobShipment> {
+            match (project, id) {
+                (1234567, 123) => Some(JobShipment::new(1234567, 'B', 3)),
+                _ => None
+            }
+        }
+    }
+
+    #[test]
+    fn test_strict() {
+        assert_eq!("D-1234567-00123".parse(), Ok(Wbs::Hd { project: 1234567, id: 123 }));
+        assert_eq!("S-1234567-2-01".parse(), Ok(Wbs::Legacy { project: 1234567, shipment: 1 }));
+
+        let cases = [
+            ("", WbsParseError::Empty),
+            (" D-1234567-00123", WbsParseError::NotNormalized(String::from(" D-1234567-00123"))),
+            ("d-1234567-00123", WbsParseError::NotNormalized(String::from("d-1234567-00123"))),
+            ("X-1234567-00123", WbsParseError::UnknownPrefix(String::from("X-1234567-00123"))),
+            ("D-123456-00123", WbsParseError::InvalidProject(String::from("D-123456-00123"))),
+            ("D-1234567-0123", WbsParseError::InvalidId(String::from("D-1234567-0123"))),
+            ("S-1234567-2-1", WbsParseError::InvalidShipment(String::from("S-1234567-2-1"))),
+            ("S-1234567-3-01", WbsParseError::ExpectedPatternMismatch(String::from("S-1234567-3-01"))),
+            ("D-1234567-00123-1", WbsParseError::ExpectedPatternMismatch(String::from("D-1234567-00123-1"))),
+        ];
+        for (value, err) in cases {
+            assert_eq!(value.parse::<Wbs>(), Err(err));
+        }
+    }
+
+    #[test]
+    fn test_lenient() {
+        assert_eq!(Wbs::parse_lenient(" d-1234567-00123\t"), Ok(Wbs::Hd { project: 1234567, id: 123 }));
+        assert_eq!(Wbs::parse_lenient("Mill: s-1234567-2-01 (old)"), Ok(Wbs::Legacy { project: 1234567, shipment: 1 }));
+
+        assert_eq!(Wbs::parse_lenient("  "), Err(WbsParseError::Empty));
+        assert_eq!(Wbs::parse_lenient("D-1234567-001234"), Err(WbsParseError::InvalidId(String::from("D-1234567-001234"))));
+        assert_eq!(Wbs::parse_lenient("xD-1234567-00123"), Err(WbsParseError::NotFound(String::from("xD-1234567-00123"))));
+
+        // only `parse_lenient` pulls a WBS element out of text
+        assert!(Wbs::try_from("Mill: S-1234567-2-01").is_err());
+    }
+
+    #[test]
+    fn test_display_leading_zeros() {
+        assert_eq!(Wbs::Hd { project: 1234567, id: 123 }.to_string(), "D-1234567-00123");
+        assert_eq!(Wbs::Legacy { project: 123, shipment: 1 }.to_string(), "S-0000123-2-01");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_hd_round_trip(project in 0u32..10_000_000, id in 0u32..100_000) {
+            let wbs = Wbs::Hd { project, id };
+            proptest::prop_assert_eq!(wbs.to_string().parse::<Wbs>(), Ok(wbs));
+        }
+
+        #[test]
+        fn prop_legacy_round_trip(project in 0u32..10_000_000, shipment in 0u32..100) {
+            let wbs = Wbs::Legacy { project, shipment };
+            proptest::prop_assert_eq!(wbs.to_string().parse::<Wbs>(), Ok(wbs));
+        }
+
+        #[test]
+        fn prop_strict_formats_as_given(value in "[DS]-[0-9]{7}-(2-)?[0-9]{2,5}") {
+            if let Ok(wbs) = value.parse::<Wbs>() {
+                proptest::prop_assert_eq!(wbs.to_string(), value);
+            }
         }
+
+        #[test]
+        fn prop_lenient_in_text(prefix in "[ a-z:]{0,8}", project in 0u32..10_000_000, id in 0u32..100_000, suffix in "[ a-z)]{0,8}") {
+            let wbs = Wbs::Hd { project, id };
+            let text = format!("{prefix} {} {suffix}", wbs.to_string().to_lowercase());
+            proptest::prop_assert_eq!(Wbs::parse_lenient(&text), Ok(wbs));
+        }
+    }
+
+    #[test]
+    fn test_job_shipment_to_wbs() {
+        let js: JobShipment = "1234567A-3".parse().unwrap();
+        assert_eq!(Wbs::try_from(&js), Ok(Wbs::Legacy { project: 1234567, shipment: 3 }));
+
+        let js: JobShipment = "1234567A-100".parse().unwrap();
+        assert_eq!(Wbs::try_from(&js), Err(WbsConversionError::ShipmentOutOfRange(js.clone())));
+
+        // converting back would change the structure to `A`
+        let js: JobShipment = "1234567B-3".parse().unwrap();
+        assert_eq!(Wbs::try_from(&js), Err(WbsConversionError::UnsupportedStructure(js.clone())));
+    }
+
+    #[test]
+    fn test_wbs_to_job_shipment() {
+        let legacy = Wbs::Legacy { project: 1234567, shipment: 3 };
+        assert_eq!(JobShipment::try_from(&legacy), Ok(JobShipment::new(1234567, 'A', 3)));
+
+        let hd = Wbs::Hd { project: 1234567, id: 123 };
+        assert_eq!(JobShipment::try_from(&hd), Err(WbsConversionError::UnmappedHardDollar(hd.clone())));
+    }
+
+    #[test]
+    fn test_hd_lookup() {
+        let hd = Wbs::Hd { project: 1234567, id: 123 };
+        assert_eq!(hd.job_shipment(&OneLine), Ok(JobShipment::new(1234567, 'B', 3)));
+
+        let unmapped = Wbs::Hd { project: 1234567, id: 124 };
+        assert_eq!(unmapped.job_shipment(&OneLine), Err(WbsConversionError::UnmappedHardDollar(unmapped.clone())));
     }
 }
@@ -0,0 +1,129 @@
+
+//! Config file discovery
+
+use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// folder, in the system and user config folders, that has the config files
+pub(super) const CONFIG_DIR: &str = "sysinteg";
+
+/// No config file was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigNotFound {
+    /// every path that was tried, in order
+    pub tried: Vec<PathBuf>,
+}
+
+impl Display for ConfigNotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "config file not found, tried:")?;
+        for path in &self.tried {
+            write!(f, "\n  - {}", path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigNotFound {}
+
+/// paths searched for a config file, in order
+///
+/// 1. the directory of the executable
+/// 2. the working directory
+/// 3. the user config directory (`%APPDATA%\sysinteg` or `~/.config/sysinteg`)
+///
+/// absolute paths are not searched for
+pub fn search_path<P: AsRef<Path>>(file: P) -> Vec<PathBuf> {
+    let file = file.as_ref();
+    if file.is_absolute() {
+        return vec![file.into()];
+    }
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let dirs = [exe_dir(), env::current_dir().ok(), user_dir().map(|dir| dir.join(CONFIG_DIR))];
+    for path in dirs.into_iter().flatten().map(|dir| dir.join(file)) {
+        // the executable is often run from its own directory
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    paths
+}
+
+/// first file that exists in the [`search_path`]
+pub fn find_config<P: AsRef<Path>>(file: P) -> Result<PathBuf, ConfigNotFound> {
+    find_first(search_path(file))
+}
+
+pub(super) fn find_first(tried: Vec<PathBuf>) -> Result<PathBuf, ConfigNotFound> {
+    match tried.iter().find(|path| path.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(ConfigNotFound { tried })
+    }
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+#[cfg(windows)]
+pub(super) fn system_dir() -> Option<PathBuf> {
+    env::var_os("ProgramData").map(PathBuf::from)
+}
+
+#[cfg(not(windows))]
+pub(super) fn system_dir() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc"))
+}
+
+#[cfg(windows)]
+pub(super) fn user_dir() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(not(windows))]
+pub(super) fn user_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_path() {
+        let paths = search_path("sysinteg_test.toml");
+
+        assert_eq!(paths[0], exe_dir().unwrap().join("sysinteg_test.toml"));
+        assert_eq!(paths[1], env::current_dir().unwrap().join("sysinteg_test.toml"));
+        assert!(paths.iter().all(|path| path.ends_with("sysinteg_test.toml")));
+    }
+
+    #[test]
+    fn test_absolute_path() {
+        let file = env::temp_dir().join("sysinteg_test.toml");
+
+        assert_eq!(search_path(&file), [file]);
+    }
+
+    #[test]
+    fn test_not_found() {
+        let err = find_config("sysinteg_does_not_exist.toml").unwrap_err();
+
+        assert_eq!(err.tried, search_path("sysinteg_does_not_exist.toml"));
+        for path in &err.tried {
+            assert!(err.to_string().contains(&path.display().to_string()));
+        }
+    }
+
+    #[test]
+    fn test_found() {
+        // the crate manifest is in the working directory when testing
+        assert_eq!(find_config("Cargo.toml").unwrap(), env::current_dir().unwrap().join("Cargo.toml"));
+    }
+}
@@ -0,0 +1,432 @@
+
+//! Annotated config generation
+
+use regex::Regex;
+use std::sync::LazyLock;
+use toml_edit::{Decor, DocumentMut, Item, Table};
+
+/// rustdoc links (i.e. ``[`DbPool`](crate::DbPool)``), which are written as their text in comments
+static DOC_LINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\([^)]*\)").expect("failed to build DOC_LINK regex"));
+
+/// Doc comments of a config's fields, used to annotate generated configs
+///
+/// Implemented by [`documented_config!`](crate::documented_config), or as an empty impl for configs without docs
+pub trait ConfigDocs {
+    /// doc comment of each field, keyed by field name
+    fn field_docs() -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// docs of the fields that are documented configs themselves (i.e. `[database]`), keyed by field name
+    fn nested_docs() -> Vec<(&'static str, DocTree)> {
+        Vec::new()
+    }
+}
+
+/// Doc comments of a config's fields, and of the configs nested in it
+#[derive(Debug, Clone)]
+pub struct DocTree {
+    fields: &'static [(&'static str, &'static str)],
+    nested: Vec<(&'static str, DocTree)>,
+}
+
+impl DocTree {
+    /// docs of a config type
+    pub fn of<T: ConfigDocs>() -> Self {
+        Self { fields: T::field_docs(), nested: T::nested_docs() }
+    }
+}
+
+/// Defines a config struct and implements [`ConfigDocs`](crate::config::ConfigDocs) from its fields' doc comments
+///
+/// Fields that are documented configs themselves are listed in `nested`, so that their fields are annotated too.
+/// ```
+/// sysinteg_core::documented_config! {
+///     #[derive(Debug, Default, serde::Serialize)]
+///     pub struct Database {
+///         /// Sigmanest database server
+///         pub server: String,
+///     }
+/// }
+///
+/// sysinteg_core::documented_config! {
+///     #[derive(Debug, Default, serde::Serialize)]
+///     pub struct Config {
+///         /// Sigmanest database
+///         pub database: Database,
+///     }
+///
+///     nested { database: Database }
+/// }
+/// ```
+#[macro_export]
+macro_rules! documented_config {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$($field_attr:tt)*])*
+                $field_vis:vis $field:ident : $ty:ty
+            ),* $(,)?
+        }
+
+        $(nested { $($nested:ident : $nested_ty:ty),* $(,)? })?
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $(
+                $(#[$($field_attr)*])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $crate::config::ConfigDocs for $name {
+            fn field_docs() -> &'static [(&'static str, &'static str)] {
+                &[$( (stringify!($field), concat!($($crate::__doc_line!($($field_attr)*)),*)) ),*]
+            }
+
+            fn nested_docs() -> Vec<(&'static str, $crate::config::DocTree)> {
+                vec![$($( (stringify!($nested), $crate::config::DocTree::of::<$nested_ty>()) ),*)?]
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __doc_line {
+    (doc = $doc:literal) => { concat!($doc, "\n") };
+    ($($other:tt)*) => { "" };
+}
+
+/// serializes a config as TOML, with each field's doc comment above it
+pub fn annotated<T: serde::Serialize + ConfigDocs>(config: &T) -> anyhow::Result<String> {
+    Ok(annotated_document(config)?.to_string())
+}
+
+/// adds the keys of `config` that are missing from `existing`, keeping everything in `existing` (including comments)
+///
+/// returns the merged TOML and the `.` separated keys that were added.
+/// Optional keys that are not set are added as comments (if they are not already), and listed as `{key} (commented out)`.
+pub fn merge<T: serde::Serialize + ConfigDocs>(existing: &str, config: &T) -> anyhow::Result<(String, Vec<String>)> {
+    let mut document = existing.parse::<DocumentMut>()
+        .map_err(|e| anyhow::anyhow!("failed to parse existing config: {e}"))?;
+
+    let mut added = Vec::new();
+    merge_table(document.as_table_mut(), annotated_document(config)?.as_table(), "", &mut added);
+
+    let mut commented = Vec::new();
+    comment_unset(document.as_table_mut(), &DocTree::of::<T>(), "", &is_commented, &mut commented);
+    added.extend(commented.into_iter().map(|key| format!("{key} (commented out)")));
+
+    Ok((document.to_string(), added))
+}
+
+fn annotated_document<T: serde::Serialize + ConfigDocs>(config: &T) -> anyhow::Result<DocumentMut> {
+    let mut document = toml::to_string(config)?.parse::<DocumentMut>()?;
+
+    let docs = DocTree::of::<T>();
+    annotate_table(document.as_table_mut(), &docs);
+    comment_unset(document.as_table_mut(), &docs, "", &|_, _| false, &mut Vec::new());
+
+    Ok(document)
+}
+
+/// puts each field's doc comment above it, in this table and the nested tables
+fn annotate_table(table: &mut Table, docs: &DocTree) {
+    for (key, doc) in docs.fields {
+        let comment = comment(doc);
+        if comment.is_empty() {
+            continue;
+        }
+
+        match table.get_mut(key) {
+            Some(Item::Table(table)) => {
+                // tables with only sub-tables (i.e. `[[plants.machine]]`) have no header to comment
+                table.set_implicit(false);
+                table.decor_mut().set_prefix(format!("\n{comment}"));
+            },
+            Some(Item::ArrayOfTables(array)) => {
+                if let Some(table) = array.get_mut(0) {
+                    table.decor_mut().set_prefix(format!("\n{comment}"));
+                }
+            },
+            Some(Item::Value(_)) => {
+                if let Some(mut key) = table.key_mut(key) {
+                    key.leaf_decor_mut().set_prefix(comment);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    for (key, docs) in &docs.nested {
+        if let Some(Item::Table(table)) = table.get_mut(key) {
+            annotate_table(table, docs);
+        }
+    }
+}
+
+/// writes the fields that are not set (i.e. `None` options) as commented out keys, above the next field that is set
+///
+/// fields that `skip` returns true for (given the table they are in) are not written,
+/// and the `.` separated keys that are written are added to `commented`
+fn comment_unset(table: &mut Table, docs: &DocTree, prefix: &str, skip: &dyn Fn(&Table, &str) -> bool, commented: &mut Vec<String>) {
+    let skipped = docs.fields.iter()
+        .filter(|(key, _)| !table.contains_key(key) && skip(table, key))
+        .map(|(key, _)| *key)
+        .collect::<Vec<_>>();
+    let mut unset = String::new();
+
+    for (key, doc) in docs.fields {
+        match table.get_mut(key) {
+            None if !skipped.contains(key) => {
+                unset.push_str(&format!("{}# {key} =\n", comment(doc)));
+                commented.push(format!("{prefix}{key}"));
+            },
+            None => (),
+            Some(_) if unset.is_empty() => (),
+            Some(Item::Table(table)) => {
+                let header = decor_prefix(table.decor());
+                table.decor_mut().set_prefix(format!("\n{unset}{}", header.trim_start_matches('\n')));
+                unset.clear();
+            },
+            Some(Item::ArrayOfTables(array)) => {
+                if let Some(table) = array.get_mut(0) {
+                    let header = decor_prefix(table.decor());
+                    table.decor_mut().set_prefix(format!("\n{unset}{}", header.trim_start_matches('\n')));
+                    unset.clear();
+                }
+            },
+            Some(_) => {
+                if let Some(mut key) = table.key_mut(key) {
+                    let leaf = decor_prefix(key.leaf_decor());
+                    key.leaf_decor_mut().set_prefix(format!("{unset}{leaf}"));
+                    unset.clear();
+                }
+            },
+        }
+    }
+
+    // fields that are not set after the last field that is, are above the table's first sub-table (if it has one)
+    if !unset.is_empty() {
+        if let Some((_, Item::Table(table))) = table.iter_mut().find(|(_, item)| item.is_table()) {
+            let header = decor_prefix(table.decor());
+            table.decor_mut().set_prefix(format!("\n{unset}{}", header.trim_start_matches('\n')));
+        }
+    }
+
+    for (key, docs) in &docs.nested {
+        if let Some(Item::Table(table)) = table.get_mut(key) {
+            comment_unset(table, docs, &format!("{prefix}{key}."), skip, commented);
+        }
+    }
+}
+
+/// if `key` is commented out in a table, above one of its keys or sub-tables
+fn is_commented(table: &Table, key: &str) -> bool {
+    let commented = format!("# {key} =");
+
+    table.iter().any(|(name, item)| {
+        let prefix = match item {
+            Item::Table(table) => decor_prefix(table.decor()),
+            Item::ArrayOfTables(array) => array.get(0).map(|table| decor_prefix(table.decor())).unwrap_or_default(),
+            _ => table.key(name).map(|key| decor_prefix(key.leaf_decor())).unwrap_or_default(),
+        };
+
+        prefix.contains(&commented)
+    })
+}
+
+/// comments and whitespace before a key or table header
+fn decor_prefix(decor: &Decor) -> String {
+    decor.prefix()
+        .and_then(|prefix| prefix.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// doc comment as TOML comment lines
+fn comment(doc: &str) -> String {
+    doc.lines()
+        .map(|line| match line.trim() {
+            "" => String::from("#\n"),
+            line => format!("# {}\n", DOC_LINK.replace_all(line, "$1")),
+        })
+        .collect()
+}
+
+fn merge_table(existing: &mut Table, defaults: &Table, prefix: &str, added: &mut Vec<String>) {
+    for (key, item) in defaults.iter() {
+        let path = format!("{prefix}{key}");
+
+        match (existing.get_mut(key), item) {
+            (None, _) => {
+                existing.insert(key, item.clone());
+                if let (Some(mut existing_key), Some(default_key)) = (existing.key_mut(key), defaults.key(key)) {
+                    *existing_key.leaf_decor_mut() = default_key.leaf_decor().clone();
+                }
+
+                added.push(path);
+            },
+            (Some(Item::Table(existing)), Item::Table(defaults)) => merge_table(existing, defaults, &format!("{path}."), added),
+            // values (and arrays of tables) that are set are kept as is
+            _ => ()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Debug, Serialize)]
+    struct Database {
+        server: String,
+        database: String,
+    }
+
+    crate::documented_config! {
+        #[derive(Debug, Serialize)]
+        struct Config {
+            /// application name used for logging
+            ///
+            /// must not be empty
+            logging_name: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            undocumented: Option<u32>,
+            /// Sigmanest database
+            database: Database,
+        }
+    }
+
+    crate::documented_config! {
+        #[derive(Debug, Serialize)]
+        struct Connection {
+            /// server name
+            server: String,
+            /// port (defaults to 1433)
+            #[serde(skip_serializing_if = "Option::is_none")]
+            port: Option<u16>,
+            /// seconds to wait for a connection (see [`Duration`](std::time::Duration))
+            #[serde(skip_serializing_if = "Option::is_none")]
+            timeout: Option<u64>,
+            /// connect read-only
+            read_only: bool,
+        }
+    }
+
+    crate::documented_config! {
+        #[derive(Debug, Serialize)]
+        struct NestedConfig {
+            /// Sigmanest database
+            database: Connection,
+        }
+
+        nested { database: Connection }
+    }
+
+    fn nested_config() -> NestedConfig {
+        NestedConfig { database: Connection { server: String::from("<server>"), port: None, timeout: None, read_only: false } }
+    }
+
+    fn config() -> Config {
+        Config {
+            logging_name: String::from("<name>"),
+            undocumented: Some(1),
+            database: Database { server: String::from("<server>"), database: String::from("<database>") }
+        }
+    }
+
+    #[test]
+    fn test_field_docs() {
+        assert_eq!(Config::field_docs(), [
+            ("logging_name", " application name used for logging\n\n must not be empty\n"),
+            ("undocumented", ""),
+            ("database", " Sigmanest database\n"),
+        ]);
+    }
+
+    #[test]
+    fn test_annotated() {
+        let toml = annotated(&config()).unwrap();
+
+        assert_eq!(toml, [
+            "# application name used for logging",
+            "#",
+            "# must not be empty",
+            "logging_name = \"<name>\"",
+            "undocumented = 1",
+            "",
+            "# Sigmanest database",
+            "[database]",
+            "server = \"<server>\"",
+            "database = \"<database>\"",
+            ""
+        ].join("\n"));
+
+        // annotated configs can be read back
+        let value: toml::Table = toml::from_str(&toml).unwrap();
+        assert_eq!(value["database"]["server"].as_str(), Some("<server>"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let existing = "# our name\nlogging_name = \"sap\"\n\n[database]\nserver = \"SQLSERV\"\n";
+        let (merged, added) = merge(existing, &config()).unwrap();
+
+        assert_eq!(added, ["undocumented", "database.database"]);
+        assert!(merged.starts_with("# our name\nlogging_name = \"sap\"\n"), "{merged}");
+        assert!(merged.contains("server = \"SQLSERV\""), "{merged}");
+
+        let value: toml::Table = toml::from_str(&merged).unwrap();
+        assert_eq!(value["database"]["database"].as_str(), Some("<database>"));
+        assert_eq!(value["undocumented"].as_integer(), Some(1));
+
+        // merging again adds nothing
+        assert!(merge(&merged, &config()).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_nested_annotated() {
+        let toml = annotated(&nested_config()).unwrap();
+
+        assert_eq!(toml, [
+            "",
+            "# Sigmanest database",
+            "[database]",
+            "# server name",
+            "server = \"<server>\"",
+            "# port (defaults to 1433)",
+            "# port =",
+            "# seconds to wait for a connection (see `Duration`)",
+            "# timeout =",
+            "# connect read-only",
+            "read_only = false",
+            ""
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_nested_merge() {
+        let existing = "[database]\nserver = \"SQLSERV\"\nread_only = true\n";
+        let (merged, added) = merge(existing, &nested_config()).unwrap();
+
+        assert_eq!(added, ["database.port (commented out)", "database.timeout (commented out)"]);
+        assert!(merged.contains("# port =\n# seconds to wait for a connection (see `Duration`)\n# timeout =\nread_only = true"), "{merged}");
+
+        // added keys have their docs, and optional keys are commented out above them
+        let (merged, added) = merge("[database]\nserver = \"SQLSERV\"\n", &nested_config()).unwrap();
+        assert_eq!(added, ["database.read_only"]);
+        assert!(merged.contains("# timeout =\n# connect read-only\nread_only = false"), "{merged}");
+
+        // merging again adds nothing
+        assert!(merge(&merged, &nested_config()).unwrap().1.is_empty());
+
+        // a key commented out in another table is still added
+        let existing = "# port =\nother = 1\n\n[database]\nserver = \"SQLSERV\"\nread_only = true\n";
+        let (_, added) = merge(existing, &nested_config()).unwrap();
+        assert_eq!(added, ["database.port (commented out)", "database.timeout (commented out)"]);
+    }
+}
@@ -0,0 +1,487 @@
+
+//! Layered configuration loading
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use super::discover::{find_first, search_path, system_dir, user_dir, ConfigNotFound, CONFIG_DIR};
+
+/// prefix of environment variables that override config values, which is followed by the app (see [`ConfigLoader::env_prefix`])
+pub const ENV_PREFIX: &str = "SYSINTEG_";
+/// separator of the app and nested keys in environment variable names (i.e. `SYSINTEG_SNDB__DATABASE__SERVER`)
+const ENV_SEPARATOR: &str = "__";
+
+/// Where a config value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// Config file
+    File(PathBuf),
+    /// Environment variable
+    Env(String),
+    /// Command line flag
+    Cli(String),
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Env(var) => write!(f, "environment variable {var}"),
+            Self::Cli(flag) => write!(f, "command line `{flag}`"),
+        }
+    }
+}
+
+/// Loads a config in layers, where each layer overrides the ones before it
+///
+/// 1. built-in defaults (`Default` impl)
+/// 2. system file (`%ProgramData%\sysinteg\{app}.toml` or `/etc/sysinteg/{app}.toml`)
+/// 3. user file (`%APPDATA%\sysinteg\{app}.toml` or `~/.config/sysinteg/{app}.toml`)
+/// 4. local file: the file given with [`ConfigLoader::config_file`] (i.e. from `--config`),
+///    otherwise the first `{app}.toml` (or [`ConfigLoader::local_file`]) in the [`search_path`](super::search_path)
+/// 5. `SYSINTEG_{APP}__*` environment variables, with `__` between nested keys (i.e. `SYSINTEG_SNDB__DATABASE__SERVER`),
+///    so that apps on the same host do not share them
+/// 6. command line overrides (`key=value`, with `.` between nested keys)
+///
+/// Environment variable and command line values are parsed as TOML values if they can be (i.e. `true` or `1433`),
+/// otherwise they are used as strings. A value that replaces a string is always a string (i.e. a database named `2024`),
+/// but values of optional keys that are not set must be quoted to be strings (i.e. `"2024"`).
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    app: String,
+    env_prefix: String,
+    local_file: PathBuf,
+    config_file: Option<PathBuf>,
+    require_file: bool,
+    defaults: Vec<(String, String)>,
+    env_aliases: Vec<(String, String)>,
+    overrides: Vec<String>,
+    env: Vec<(String, String)>,
+}
+
+/// Config loaded by a [`ConfigLoader`], with the source of each value
+#[derive(Debug)]
+pub struct LoadedConfig<T> {
+    /// Effective config
+    pub config: T,
+    values: Table,
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ConfigLoader {
+    /// loader for an application's config
+    pub fn new(app: &str) -> Self {
+        Self {
+            app: app.into(),
+            env_prefix: format!("{ENV_PREFIX}{}{ENV_SEPARATOR}", app.to_uppercase()),
+            local_file: PathBuf::from(format!("{app}.toml")),
+            config_file: None,
+            require_file: false,
+            defaults: Vec::new(),
+            env_aliases: Vec::new(),
+            overrides: Vec::new(),
+            env: env::vars().collect(),
+        }
+    }
+
+    /// prefix of the environment variables that override this app's config (i.e. `SYSINTEG_SNDB__`)
+    pub fn env_prefix(&self) -> &str {
+        &self.env_prefix
+    }
+
+    /// sets the local config file, which is searched for in the [`search_path`](super::search_path)
+    pub fn local_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.local_file = path.into();
+        self
+    }
+
+    /// local config file given explicitly (i.e. with `--config`), which must exist
+    pub fn config_file<P: Into<PathBuf>>(mut self, path: Option<P>) -> Self {
+        self.config_file = path.map(Into::into);
+        self
+    }
+
+    /// fail with [`ConfigNotFound`] if no config file exists
+    pub fn require_file(mut self) -> Self {
+        self.require_file = true;
+        self
+    }
+
+    /// application default for `key`, which replaces the config type's default (i.e. a read-only connection for a reporting tool)
+    pub fn default_value(mut self, key: &str, value: &str) -> Self {
+        self.defaults.push((key.into(), value.into()));
+        self
+    }
+
+    /// also read config `key` from the environment variable `var` (i.e. for legacy variable names)
+    ///
+    /// aliases are overridden by `SYSINTEG_{APP}__*` variables
+    pub fn env_alias(mut self, var: &str, key: &str) -> Self {
+        self.env_aliases.push((var.into(), key.into()));
+        self
+    }
+
+    /// command line overrides, as `key=value`
+    pub fn overrides<S: AsRef<str>>(mut self, overrides: &[S]) -> Self {
+        self.overrides.extend(overrides.iter().map(|s| s.as_ref().to_string()));
+        self
+    }
+
+    /// system and user config files, in the order they are loaded
+    pub fn files(&self) -> Vec<PathBuf> {
+        let file_name = format!("{}.toml", self.app);
+
+        [system_dir(), user_dir()].into_iter()
+            .flatten()
+            .map(|dir| dir.join(CONFIG_DIR).join(&file_name))
+            .collect()
+    }
+
+    /// local config file, if it exists
+    ///
+    /// if the file was given with [`ConfigLoader::config_file`] and does not exist, this is an error
+    pub fn find_local_file(&self) -> Result<PathBuf, ConfigNotFound> {
+        match &self.config_file {
+            Some(path) => find_first(vec![path.clone()]),
+            None => find_first(search_path(&self.local_file))
+        }
+    }
+
+    /// loads the config
+    pub fn load<T>(&self) -> anyhow::Result<LoadedConfig<T>>
+        where T: Default + serde::Serialize + serde::de::DeserializeOwned
+    {
+        let defaults = match Value::try_from(T::default())? {
+            Value::Table(table) => table,
+            _ => anyhow::bail!("config for `{}` is not a table", self.app)
+        };
+
+        let mut loaded = LoadedConfig { config: T::default(), values: Table::new(), sources: BTreeMap::new() };
+        loaded.merge(defaults, &ConfigSource::Default)?;
+        for (key, value) in &self.defaults {
+            loaded.set_parsed(key, value, ConfigSource::Default)?;
+        }
+
+        let mut tried = Vec::new();
+        let mut found = false;
+        for path in self.files() {
+            if !path.is_file() {
+                log::trace!("Config file {} does not exist", path.display());
+                tried.push(path);
+                continue;
+            }
+
+            loaded.merge(read_file(&path)?, &ConfigSource::File(path))?;
+            found = true;
+        }
+
+        match self.find_local_file() {
+            Ok(path) => {
+                log::debug!("Using config file {}", path.display());
+                loaded.merge(read_file(&path)?, &ConfigSource::File(path))?;
+            },
+            // an explicit config file must exist
+            Err(e) if self.config_file.is_some() => return Err(e.into()),
+            Err(mut e) if self.require_file && !found => {
+                tried.append(&mut e.tried);
+                return Err(ConfigNotFound { tried }.into());
+            },
+            Err(e) => log::trace!("{e}")
+        }
+
+        for (var, key) in &self.env_aliases {
+            if let Some((_, value)) = self.env.iter().find(|(name, _)| name == var) {
+                loaded.set_parsed(key, value, ConfigSource::Env(var.clone()))?;
+            }
+        }
+
+        for (var, value) in &self.env {
+            if let Some(key) = var.strip_prefix(&self.env_prefix) {
+                let key = key.to_lowercase().replace(ENV_SEPARATOR, ".");
+                loaded.set_parsed(&key, value, ConfigSource::Env(var.clone()))?;
+            }
+        }
+
+        for flag in &self.overrides {
+            match flag.split_once('=') {
+                Some((key, value)) => loaded.set_parsed(key.trim(), value.trim(), ConfigSource::Cli(flag.clone()))?,
+                None => anyhow::bail!("config override `{flag}` is not of the form `key=value`")
+            }
+        }
+
+        loaded.config = Value::Table(loaded.values.clone()).try_into()
+            .map_err(|e| anyhow::anyhow!("invalid config for `{}`: {e}", self.app))?;
+
+        Ok(loaded)
+    }
+}
+
+impl<T> LoadedConfig<T> {
+    /// the effective config
+    pub fn into_inner(self) -> T {
+        self.config
+    }
+
+    /// where a value came from (`.` between nested keys)
+    pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+        self.sources.get(key)
+    }
+
+    /// each value of the effective config, and where it came from
+    pub fn explain(&self) -> String {
+        self.sources.iter()
+            .filter_map(|(key, source)| get(&self.values, key).map(|value| format!("{key} = {value}  # {source}")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn merge(&mut self, layer: Table, source: &ConfigSource) -> anyhow::Result<()> {
+        for (key, value) in flatten(layer) {
+            self.set(&key, value, source.clone())
+                .map_err(|e| anyhow::anyhow!("{e} (from {source})"))?;
+        }
+
+        Ok(())
+    }
+
+    /// set a value from text (an environment variable or command line flag)
+    ///
+    /// the text is parsed as TOML, unless the value it replaces is a string (i.e. a database named `2024`)
+    fn set_parsed(&mut self, key: &str, value: &str, source: ConfigSource) -> anyhow::Result<()> {
+        let value = match (get(&self.values, key), parse_value(value)) {
+            (Some(Value::String(_)), parsed) if !parsed.is_str() => Value::String(value.into()),
+            (_, parsed) => parsed
+        };
+
+        self.set(key, value, source)
+    }
+
+    fn set(&mut self, key: &str, value: Value, source: ConfigSource) -> anyhow::Result<()> {
+        let mut path = key.split('.').peekable();
+        let mut table = &mut self.values;
+
+        while let Some(part) = path.next() {
+            if part.is_empty() {
+                anyhow::bail!("config key `{key}` is not valid");
+            }
+
+            if path.peek().is_none() {
+                table.insert(part.into(), value);
+                break;
+            }
+
+            let entry = table.entry(part).or_insert_with(|| Value::Table(Table::new()));
+            table = match entry {
+                Value::Table(table) => table,
+                _ => anyhow::bail!("config key `{key}` is nested in a value that is not a table")
+            };
+        }
+
+        // a value replaces everything under it
+        let prefix = format!("{key}.");
+        self.sources.retain(|k, _| !k.starts_with(&prefix));
+        self.sources.insert(key.into(), source);
+
+        Ok(())
+    }
+}
+
+/// leaf values of a table, keyed by their `.` separated path (arrays are leaves)
+fn flatten(table: Table) -> Vec<(String, Value)> {
+    table.into_iter()
+        .flat_map(|(key, value)| match value {
+            Value::Table(table) => flatten(table).into_iter()
+                .map(|(k, v)| (format!("{key}.{k}"), v))
+                .collect(),
+            value => vec![(key, value)]
+        })
+        .collect()
+}
+
+fn get<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (first, rest) = match key.split_once('.') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (key, None)
+    };
+
+    match (table.get(first)?, rest) {
+        (Value::Table(table), Some(rest)) => get(table, rest),
+        (value, None) => Some(value),
+        _ => None
+    }
+}
+
+fn read_file(path: &Path) -> anyhow::Result<Table> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read config file {}: {e}", path.display()))?;
+
+    toml::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("failed to parse config file {}: {e}", path.display()))
+}
+
+/// value as TOML, if it is valid TOML, otherwise a string
+fn parse_value(value: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Config {
+        name: String,
+        database: Database,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Database {
+        server: String,
+        port: u16,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                name: String::from("default"),
+                database: Database { server: String::from("<server>"), port: 1433 }
+            }
+        }
+    }
+
+    fn loader(env: &[(&str, &str)]) -> ConfigLoader {
+        let mut loader = ConfigLoader::new("sysinteg_test").local_file("does_not_exist.toml");
+        loader.env = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+        loader
+    }
+
+    #[test]
+    fn test_defaults() {
+        let loaded = loader(&[]).load::<Config>().unwrap();
+
+        assert_eq!(loaded.config.database.server, "<server>");
+        assert_eq!(loaded.source("database.port"), Some(&ConfigSource::Default));
+
+        let loaded = loader(&[("SYSINTEG_SYSINTEG_TEST__NAME", "env")]).default_value("database.port", "1500").default_value("name", "app").load::<Config>().unwrap();
+
+        assert_eq!(loaded.config.database.port, 1500);
+        assert_eq!(loaded.source("database.port"), Some(&ConfigSource::Default));
+        assert_eq!(loaded.config.name, "env");
+    }
+
+    #[test]
+    fn test_layers() {
+        let loaded = loader(&[("SYSINTEG_SYSINTEG_TEST__DATABASE__SERVER", "env-server"), ("SYSINTEG_SYSINTEG_TEST__DATABASE__PORT", "1500"), ("SndbDatabase", "x")])
+            .env_alias("SndbServer", "database.server")
+            .overrides(&["database.port = 1600"])
+            .load::<Config>()
+            .unwrap();
+
+        assert_eq!(loaded.config.database.server, "env-server");
+        assert_eq!(loaded.source("database.server"), Some(&ConfigSource::Env(String::from("SYSINTEG_SYSINTEG_TEST__DATABASE__SERVER"))));
+        assert_eq!(loaded.config.database.port, 1600);
+        assert_eq!(loaded.source("database.port"), Some(&ConfigSource::Cli(String::from("database.port = 1600"))));
+    }
+
+    #[test]
+    fn test_env_per_app() {
+        // two apps on the same host, with the environment of both
+        let env = [("SYSINTEG_SNDB__DATABASE__SERVER", "sndb-server"), ("SYSINTEG_SAP_CONSUMPTION__DATABASE__SERVER", "sap-server")];
+        let load = |app| {
+            let mut loader = ConfigLoader::new(app).local_file("does_not_exist.toml");
+            loader.env = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+            loader.load::<Config>().unwrap().config
+        };
+
+        assert_eq!(load("sndb").database.server, "sndb-server");
+        assert_eq!(load("sap_consumption").database.server, "sap-server");
+        assert_eq!(ConfigLoader::new("sap_consumption").env_prefix(), "SYSINTEG_SAP_CONSUMPTION__");
+
+        // an app whose name starts another app's name does not read its variables
+        assert_eq!(load("sap").database.server, "<server>");
+    }
+
+    #[test]
+    fn test_env_alias() {
+        let loaded = loader(&[("SndbServer", "legacy")])
+            .env_alias("SndbServer", "database.server")
+            .load::<Config>()
+            .unwrap();
+
+        assert_eq!(loaded.config.database.server, "legacy");
+    }
+
+    #[test]
+    fn test_explain() {
+        let loaded = loader(&[]).overrides(&["name=cli"]).load::<Config>().unwrap();
+
+        assert_eq!(loaded.explain(), [
+            "database.port = 1433  # default",
+            "database.server = \"<server>\"  # default",
+            "name = \"cli\"  # command line `name=cli`",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        assert!(loader(&[]).overrides(&["name"]).load::<Config>().is_err());
+        assert!(loader(&[]).overrides(&["name.first=x"]).load::<Config>().is_err());
+        assert!(loader(&[]).overrides(&["database.port=not a port"]).load::<Config>().is_err());
+    }
+
+    #[test]
+    fn test_config_file() {
+        let err = loader(&[]).config_file(Some("does_not_exist.toml")).load::<Config>().unwrap_err();
+        assert_eq!(err.downcast::<ConfigNotFound>().unwrap().tried, [PathBuf::from("does_not_exist.toml")]);
+
+        let err = loader(&[]).require_file().load::<Config>().unwrap_err().to_string();
+        assert!(err.contains("does_not_exist.toml"), "{err}");
+    }
+
+    #[test]
+    fn test_string_values() {
+        let loaded = loader(&[("SYSINTEG_SYSINTEG_TEST__NAME", "2024")]).load::<Config>().unwrap();
+        assert_eq!(loaded.config.name, "2024");
+
+        let loaded = loader(&[]).overrides(&["name=1.5", "database.server=\"quoted\"", "database.port=1500"]).load::<Config>().unwrap();
+        assert_eq!(loaded.config.name, "1.5");
+        assert_eq!(loaded.config.database.server, "quoted");
+        assert_eq!(loaded.config.database.port, 1500);
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let dir = std::env::temp_dir().join("sysinteg_test_invalid_file");
+        fs::create_dir_all(&dir).unwrap();
+
+        // a table where the config has a value, and an empty key, are errors (not panics) that name the file
+        for (name, contents) in [("table.toml", "[name]\nfirst = \"x\""), ("empty_key.toml", "\"\" = 1")] {
+            let path = dir.join(name);
+            fs::write(&path, contents).unwrap();
+
+            let err = loader(&[]).config_file(Some(&path)).load::<Config>().unwrap_err().to_string();
+            assert!(err.contains(name), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("1433"), Value::Integer(1433));
+        assert_eq!(parse_value("true"), Value::Boolean(true));
+        assert_eq!(parse_value(r"\\server\share"), Value::String(String::from(r"\\server\share")));
+    }
+}
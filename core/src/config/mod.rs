@@ -0,0 +1,80 @@
+
+//! db configurations from local `.toml` files
+
+mod discover;
+mod docs;
+mod layered;
+mod validate;
+
+pub use docs::{annotated, merge, ConfigDocs, DocTree};
+pub use discover::{find_config, search_path, ConfigNotFound};
+pub use layered::{ConfigLoader, ConfigSource, LoadedConfig};
+pub use validate::{Validate, Validation, ValidationError, ValidationErrors};
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    io::Write
+};
+
+/// trait to allow for easy serde of a config
+pub trait TomlConfig {
+    /// load the configuration from `config.toml` in the root directory
+    fn load<T: Into<PathBuf>>(path: T) -> anyhow::Result<Self>
+        where Self: Sized + serde::de::DeserializeOwned
+    {
+        // read file
+        let path = path.into();
+        let toml_contents = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("failed to read config file {}: {e}", path.display()))?;
+
+        // parse toml file text
+        let parsed = toml::from_str::<Self>(&toml_contents)?;
+
+        Ok(parsed)
+    }
+
+    /// load the configuration from the first `file` found in the [`search_path`]
+    fn discover<T: AsRef<Path>>(file: T) -> anyhow::Result<Self>
+        where Self: Sized + serde::de::DeserializeOwned
+    {
+        Self::load(find_config(file)?)
+    }
+
+    /// generate an example config, with each field's doc comment (see [`ConfigDocs`])
+    fn generate<T: Into<PathBuf>>(path: T) -> anyhow::Result<()>
+        where Self: Sized + Default + serde::Serialize + ConfigDocs
+    {
+        let toml = annotated(&Self::default())?;
+        
+        let mut file = File::create(path.into())?;
+        file.write_all(toml.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// add the keys missing from an existing config (i.e. after an upgrade), without changing any that are set
+    ///
+    /// returns the `.` separated keys that were added
+    fn merge<T: Into<PathBuf>>(path: T) -> anyhow::Result<Vec<String>>
+        where Self: Sized + Default + serde::Serialize + ConfigDocs
+    {
+        let path = path.into();
+        let existing = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("failed to read config file {}: {e}", path.display()))?;
+
+        let (toml, added) = merge(&existing, &Self::default())?;
+        if !added.is_empty() {
+            fs::write(&path, toml)?;
+        }
+
+        Ok(added)
+    }
+
+    /// load the configuration in layers (see [`ConfigLoader`])
+    fn load_layered(loader: &ConfigLoader) -> anyhow::Result<LoadedConfig<Self>>
+        where Self: Sized + Default + serde::Serialize + serde::de::DeserializeOwned
+    {
+        loader.load()
+    }
+}
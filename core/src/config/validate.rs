@@ -0,0 +1,228 @@
+
+//! Config validation
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+// `<...>` that is not a regex named group (`(?<name>` or `(?P<name>`)
+static PLACEHOLDER_PATTERN: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"(?:^|[^?P])(<[^<>]+>)").expect("failed to build PLACEHOLDER_PATTERN regex"));
+
+/// file created to check if a directory is writable
+const WRITE_TEST_FILE: &str = ".sysinteg_write_test";
+
+/// Config that can be checked before it is used
+pub trait Validate {
+    /// report every invalid field to `report`
+    fn validate_into(&self, report: &mut Validation);
+
+    /// check the config, returning every invalid field
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut report = Validation::default();
+        self.validate_into(&mut report);
+
+        report.into_result()
+    }
+}
+
+/// Invalid config field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// `.` separated path of the field (i.e. `database.server`)
+    pub path: String,
+    /// what is wrong with the field
+    pub message: String,
+}
+
+/// Every invalid field of a config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+/// Collects [`ValidationError`]s, keeping track of the path of the field being checked
+#[derive(Debug, Default)]
+pub struct Validation {
+    path: Vec<String>,
+    errors: Vec<ValidationError>,
+}
+
+impl Validation {
+    /// validate a nested config
+    pub fn nested<V: Validate + ?Sized>(&mut self, name: &str, value: &V) -> &mut Self {
+        self.path.push(name.into());
+        value.validate_into(self);
+        self.path.pop();
+
+        self
+    }
+
+    /// report an invalid field
+    pub fn error<S: Into<String>>(&mut self, name: &str, message: S) -> &mut Self {
+        let path = self.path.iter()
+            .map(String::as_str)
+            .chain([name])
+            .collect::<Vec<_>>()
+            .join(".");
+
+        self.errors.push(ValidationError { path, message: message.into() });
+
+        self
+    }
+
+    /// field is not empty (or whitespace)
+    pub fn not_empty(&mut self, name: &str, value: &str) -> &mut Self {
+        if value.trim().is_empty() {
+            self.error(name, "is empty");
+        }
+
+        self
+    }
+
+    /// field is not (or does not contain) a `<placeholder>` from a generated config
+    pub fn not_placeholder(&mut self, name: &str, value: &str) -> &mut Self {
+        if let Some(placeholder) = placeholder(value) {
+            self.error(name, format!("has placeholder `{placeholder}`"));
+        }
+
+        self
+    }
+
+    /// field is set (not empty or a placeholder)
+    pub fn required(&mut self, name: &str, value: &str) -> &mut Self {
+        match value.trim().is_empty() {
+            true  => self.not_empty(name, value),
+            false => self.not_placeholder(name, value),
+        }
+    }
+
+    /// field is a directory that exists and can be written to
+    pub fn writable_dir(&mut self, name: &str, path: &Path) -> &mut Self {
+        if let Some(placeholder) = placeholder(&path.to_string_lossy()) {
+            return self.error(name, format!("has placeholder `{placeholder}`"));
+        }
+
+        if !path.is_dir() {
+            return self.error(name, format!("directory `{}` does not exist", path.display()));
+        }
+
+        let test_file = path.join(WRITE_TEST_FILE);
+        match fs::write(&test_file, b"") {
+            Ok(_) => { let _ = fs::remove_file(test_file); },
+            Err(e) => { self.error(name, format!("directory `{}` is not writable: {e}", path.display())); }
+        }
+
+        self
+    }
+
+    /// errors found
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// `Ok` if no errors were found
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        match self.errors.is_empty() {
+            true  => Ok(()),
+            false => Err(ValidationErrors(self.errors)),
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` {}", self.path, self.message)
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid config:")?;
+        for error in &self.0 {
+            write!(f, "\n  - {error}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+fn placeholder(value: &str) -> Option<&str> {
+    PLACEHOLDER_PATTERN.captures(value)
+        .and_then(|caps| caps.get(1))
+        .map(|placeholder| placeholder.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Database {
+        server: String,
+        database: String,
+    }
+
+    struct Config {
+        database: Database,
+        name: String,
+    }
+
+    impl Validate for Database {
+        fn validate_into(&self, report: &mut Validation) {
+            report
+                .required("server", &self.server)
+                .required("database", &self.database);
+        }
+    }
+
+    impl Validate for Config {
+        fn validate_into(&self, report: &mut Validation) {
+            report
+                .nested("database", &self.database)
+                .required("name", &self.name);
+        }
+    }
+
+    #[test]
+    fn test_paths() {
+        let config = Config {
+            database: Database { server: String::from("<server>"), database: String::from("SNDB") },
+            name: String::from(" "),
+        };
+
+        assert_eq!(config.validate().unwrap_err().0, vec![
+            ValidationError { path: String::from("database.server"), message: String::from("has placeholder `<server>`") },
+            ValidationError { path: String::from("name"), message: String::from("is empty") },
+        ]);
+    }
+
+    #[test]
+    fn test_valid() {
+        let config = Config {
+            database: Database { server: String::from("HSSSQLSERV"), database: String::from("SNDB") },
+            name: String::from("sap_consumption"),
+        };
+
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_placeholder() {
+        assert_eq!(placeholder(r"\\<server>\<path to files>"), Some("<server>"));
+        assert_eq!(placeholder("<machine name pattern>"), Some("<machine name pattern>"));
+        assert_eq!(placeholder(r"^(?P<plant>\d)_(?<machine>\w+)$"), None);
+        assert_eq!(placeholder("Plant_3"), None);
+    }
+
+    #[test]
+    fn test_writable_dir() {
+        let mut report = Validation::default();
+        report
+            .writable_dir("output_dir", &std::env::temp_dir())
+            .writable_dir("missing_dir", Path::new("does/not/exist"))
+            .writable_dir("placeholder_dir", Path::new(r"\\<server>\<path>"));
+
+        let paths: Vec<_> = report.errors().iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["missing_dir", "placeholder_dir"]);
+    }
+}
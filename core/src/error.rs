@@ -0,0 +1,110 @@
+
+//! Error types
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::api::{
+    ClassifyError,
+    JobShipmentParseError,
+    MaterialMasterParseError,
+    PartParseError,
+    PlantParseError,
+    ProgramNameParseError,
+    SheetNameParseError,
+    UnitOfMeasureParseError,
+    UomConversionError,
+    WbsConversionError,
+    WbsParseError,
+};
+
+/// Result type for sysinteg-core
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors for sysinteg-core
+#[derive(Debug)]
+pub enum Error {
+    /// Invalid job and shipment
+    JobShipment(JobShipmentParseError),
+    /// Invalid material master
+    MaterialMaster(MaterialMasterParseError),
+    /// Invalid part mark
+    Part(PartParseError),
+    /// Invalid plant
+    Plant(PlantParseError),
+    /// Invalid program name
+    ProgramName(ProgramNameParseError),
+    /// Invalid sheet name
+    SheetName(SheetNameParseError),
+    /// Invalid unit of measure
+    UnitOfMeasure(UnitOfMeasureParseError),
+    /// Quantity cannot be converted to another unit of measure
+    UomConversion(UomConversionError),
+    /// Invalid WBS element
+    Wbs(WbsParseError),
+    /// WBS element cannot be converted to or from a job and shipment
+    WbsConversion(WbsConversionError),
+    /// Identifier could not be classified
+    Classify(ClassifyError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::JobShipment(e)    => write!(f, "{e}"),
+            Self::MaterialMaster(e) => write!(f, "{e}"),
+            Self::Part(e)           => write!(f, "{e}"),
+            Self::Plant(e)          => write!(f, "{e}"),
+            Self::ProgramName(e)    => write!(f, "{e}"),
+            Self::SheetName(e)      => write!(f, "{e}"),
+            Self::UnitOfMeasure(e)  => write!(f, "{e}"),
+            Self::UomConversion(e)  => write!(f, "{e}"),
+            Self::Wbs(e)            => write!(f, "{e}"),
+            Self::WbsConversion(e)  => write!(f, "{e}"),
+            Self::Classify(e)       => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::JobShipment(e)    => Some(e),
+            Self::MaterialMaster(e) => Some(e),
+            Self::Part(e)           => Some(e),
+            Self::Plant(e)          => Some(e),
+            Self::ProgramName(e)    => Some(e),
+            Self::SheetName(e)      => Some(e),
+            Self::UnitOfMeasure(e)  => Some(e),
+            Self::UomConversion(e)  => Some(e),
+            Self::Wbs(e)            => Some(e),
+            Self::WbsConversion(e)  => Some(e),
+            Self::Classify(e)       => Some(e),
+        }
+    }
+}
+
+macro_rules! impl_from_error {
+    ($($variant:ident($err:ty)),+ $(,)?) => {
+        $(
+            impl From<$err> for Error {
+                fn from(value: $err) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_error!(
+    JobShipment(JobShipmentParseError),
+    MaterialMaster(MaterialMasterParseError),
+    Part(PartParseError),
+    Plant(PlantParseError),
+    ProgramName(ProgramNameParseError),
+    SheetName(SheetNameParseError),
+    UnitOfMeasure(UnitOfMeasureParseError),
+    UomConversion(UomConversionError),
+    Wbs(WbsParseError),
+    WbsConversion(WbsConversionError),
+    Classify(ClassifyError),
+);
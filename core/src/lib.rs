@@ -5,3 +5,6 @@
 
 pub mod api;
 pub mod config;
+mod error;
+
+pub use error::{Error, Result};
@@ -16,7 +16,11 @@ AS
 				Program.ProgramName,
 				Program.PostDateTime AS Timestamp,
 				Stock.SheetName,
-				Stock.PrimeCode AS MaterialMaster
+				Stock.PrimeCode AS MaterialMaster,
+				Stock.Thickness,
+				Stock.Width,
+				Stock.Length,
+				Stock.Area AS RemainingArea
 			FROM Program
 			INNER JOIN Stock
 				ON Stock.SheetName = Program.SheetName
@@ -41,6 +45,10 @@ AS
 				Stock.HeatNumber,
 				LEFT(Stock.BinNumber, 10) AS PoNumber,
 				NULLIF(Stock.Mill, '') AS Wbs,
+				Stock.Thickness,
+				Stock.Width,
+				Stock.Length,
+				Stock.Area AS RemainingArea,
 				NULLIF(CompletedProgram.OperatorName, '') AS Operator
 			FROM ProgArchive AS Program
 			LEFT OUTER JOIN StockArchive AS Stock
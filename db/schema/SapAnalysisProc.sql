@@ -14,7 +14,8 @@ CREATE PROCEDURE SapAnalysis_PrevWeek AS
 		MaterialLocation AS Location,
 		MaterialMaster,
 		MaterialWbs AS Wbs,
-		Plant,
+		-- the plant is mapped from the machine by the `plants` config of sap_consumption
+		MachineName AS Machine,
 		'' AS OrderOrDocument,
 		'' AS SAPValue,
 		'' AS Notes
@@ -41,6 +41,7 @@ CREATE VIEW SapConsumptionData AS
 				ArchivePacketID,
 				PrimeCode AS Material,
 				Mill as Wbs,
+				Thickness,
 				Location
 			FROM StockArchive
 		),
@@ -48,11 +49,7 @@ CREATE VIEW SapConsumptionData AS
 			SELECT
 				ArchivePacketID,
 				ArcDateTime,
-				CASE LEFT(MachineName,7)
-					WHEN 'Plant_3'
-						THEN 'HS02'
-						ELSE 'HS01'
-				END AS Plant,
+				MachineName, -- mapped to a plant by sap_consumption (see `plants` in its config)
 				ProgramName
 			FROM ProgArchive
 			WHERE TransType = 'SN102'
@@ -72,8 +69,9 @@ CREATE VIEW SapConsumptionData AS
 		Sheets.Wbs AS MaterialWbs,
 		ROUND(Parts.AreaPerEach * Parts.Qty, 3) AS TotalNestedArea,
 		'IN2' AS MaterialUoM,
+		Sheets.Thickness AS MaterialThickness, -- used by sap_consumption to convert to weight, and not output
 		Sheets.Location AS MaterialLocation,
-		Programs.Plant,
+		Programs.MachineName,
 		Programs.ProgramName
 	FROM Parts
 		INNER JOIN Sheets
@@ -95,8 +93,9 @@ AS
 		MaterialWbs,
 		TotalNestedArea,
 		MaterialUoM,
+		MaterialThickness,
 		MaterialLocation,
-		Plant,
+		MachineName,
 		ProgramName
 	FROM SapConsumptionData
 GO
@@ -117,8 +116,9 @@ AS
 		MaterialWbs,
 		TotalNestedArea,
 		MaterialUoM,
+		MaterialThickness,
 		MaterialLocation,
-		Plant,
+		MachineName,
 		ProgramName
 	FROM SapConsumptionData
 	WHERE ArcDateTime >= @Start AND ArcDateTime < @End
@@ -131,46 +131,18 @@ CREATE PROCEDURE SapIssueData
 AS
 	DECLARE @WbsPattern varchar(64) = 'D-' + REPLICATE('[0-9]', 7) + '-' + REPLICATE('[0-9]', 5); -- regex: D-\d{7}-\d{5}
 
+	-- Code, User1 and User2 are set from PartName, Job and Shipment by sap_consumption (see `IssueCode`)
 	SELECT
-		CASE
-			WHEN Shipment LIKE '20[0-9][0-9]' THEN
-				CASE MaterialWbs WHEN ''
-					THEN 'CC01'
-					ELSE 'CC02'
-				END
-			WHEN MaterialWbs LIKE 'D-' + Job + '-%'
-				THEN 'PR01'
-			WHEN MaterialWbs = ''
-				THEN 'PR02'
-			ELSE 'PR03'	-- Sheets.Wbs is for a different Job
-		END AS Code,
-		CASE
-			WHEN Shipment LIKE '20[0-9][0-9]'
-				THEN Shipment
-				ELSE 'D-' + Job
-		END AS User1,
-		CASE WHEN Shipment LIKE '20[0-9][0-9]' THEN
-			CASE WHEN
-				-- wish there was a better way to do this,
-				--   but this is the same as the regex `(^|[_-])machine($|[_-])` for each machine name
-				PartName LIKE 'gemini[-_]%' OR PartName LIKE '%[-_]gemini' OR PartName LIKE '%[-_]gemini[-_]%' OR
-				PartName LIKE 'titan[-_]%'  OR PartName LIKE '%[-_]titan'  OR PartName LIKE '%[-_]titan[-_]%'  OR
-				PartName LIKE 'mg[-_]%'     OR PartName LIKE '%[-_]mg'     OR PartName LIKE '%[-_]mg[-_]%'     OR
-				PartName LIKE 'farley[-_]%' OR PartName LIKE '%[-_]farley' OR PartName LIKE '%[-_]farley[-_]%' OR
-				PartName LIKE 'ficep[-_]%'  OR PartName LIKE '%[-_]ficep'  OR PartName LIKE '%[-_]ficep[-_]%'
-
-				THEN '634124' -- machine parts
-				ELSE '637118' -- shop supplies
-			END
-
-			ELSE Shipment
-		END AS User2,
+		PartName,
+		Job,
+		Shipment,
 		MaterialMaster,
 		MaterialWbs,
 		TotalNestedArea,
 		MaterialUoM,
+		MaterialThickness,
 		MaterialLocation,
-		Plant,
+		MachineName,
 		Id
 	FROM SapConsumptionData
 	WHERE ArcDateTime >= @Start AND ArcDateTime < @End
@@ -196,46 +168,18 @@ GO
 
 CREATE VIEW SapIssueData_Raw
 AS
+	-- Code, User1 and User2 are set from PartName, Job and Shipment by sap_consumption (see `IssueCode`)
 	SELECT
-		CASE
-			WHEN Shipment LIKE '20[0-9][0-9]' THEN
-				CASE MaterialWbs WHEN ''
-					THEN 'CC01'
-					ELSE 'CC02'
-				END
-			WHEN MaterialWbs LIKE 'D-' + Job + '-%'
-				THEN 'PR01'
-			WHEN MaterialWbs = ''
-				THEN 'PR02'
-			ELSE 'PR03'	-- Sheets.Wbs is for a different Job
-		END AS Code,
-		CASE
-			WHEN Shipment LIKE '20[0-9][0-9]'
-				THEN Shipment
-				ELSE 'D-' + Job
-		END AS User1,
-		CASE WHEN Shipment LIKE '20[0-9][0-9]' THEN
-			CASE WHEN
-				-- wish there was a better way to do this,
-				--   but this is the same as the regex `(^|[_-])machine($|[_-])` for each machine name
-				PartName LIKE 'gemini[-_]%' OR PartName LIKE '%[-_]gemini' OR PartName LIKE '%[-_]gemini[-_]%' OR
-				PartName LIKE 'titan[-_]%'  OR PartName LIKE '%[-_]titan'  OR PartName LIKE '%[-_]titan[-_]%'  OR
-				PartName LIKE 'mg[-_]%'     OR PartName LIKE '%[-_]mg'     OR PartName LIKE '%[-_]mg[-_]%'     OR
-				PartName LIKE 'farley[-_]%' OR PartName LIKE '%[-_]farley' OR PartName LIKE '%[-_]farley[-_]%' OR
-				PartName LIKE 'ficep[-_]%'  OR PartName LIKE '%[-_]ficep'  OR PartName LIKE '%[-_]ficep[-_]%'
-
-				THEN '634124' -- machine parts
-				ELSE '637118' -- shop supplies
-			END
-
-			ELSE Shipment
-		END AS User2,
+		PartName,
+		Job,
+		Shipment,
 		MaterialMaster,
 		MaterialWbs,
 		TotalNestedArea,
 		MaterialUoM,
+		MaterialThickness,
 		MaterialLocation,
-		Plant,
+		MachineName,
 		Id
 	FROM SapConsumptionData;
 GO
@@ -243,7 +187,20 @@ GO
 CREATE PROCEDURE SapIssueData_ForInboxFailure
 	@Mark VARCHAR(32), @Wbs VARCHAR(16), @Qty INTEGER, @Program VARCHAR(8)
 AS
-	SELECT * FROM SapIssueData_Raw
+	-- columns are listed so a change to the view does not change what this returns
+	SELECT
+		PartName,
+		Job,
+		Shipment,
+		MaterialMaster,
+		MaterialWbs,
+		TotalNestedArea,
+		MaterialUoM,
+		MaterialThickness,
+		MaterialLocation,
+		MachineName,
+		Id
+	FROM SapIssueData_Raw
 	WHERE Id IN (
 		SELECT Id FROM SapConsumptionData
 		WHERE PartName = @Mark
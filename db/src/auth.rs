@@ -0,0 +1,208 @@
+
+//! SQL Server authentication and TLS options
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use tiberius::{AuthMethod, Config, EncryptionLevel};
+
+use sysinteg_core::config::{Validate, Validation};
+
+use crate::{DbResult, Error};
+
+/// How to authenticate to SQL Server
+/// ```toml
+/// [database.auth]
+/// method = "sql_login"
+/// user = "sndb_reader"
+/// password_env = "SNDB_PASSWORD"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum DbAuth {
+    /// Windows integrated authentication, as the user running the application (Windows only)
+    #[default]
+    Integrated,
+    /// SQL Server login, with the password from an environment variable or a secret file
+    SqlLogin {
+        /// Login name
+        user: String,
+        /// Environment variable that has the password
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        password_env: Option<String>,
+        /// File that has the password (i.e. a mounted secret)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        password_file: Option<PathBuf>,
+    },
+    /// Kerberos, using the current ticket (needs the `kerberos` feature on Linux)
+    Kerberos,
+}
+
+/// Encryption of the connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encryption {
+    /// Encrypt everything, and fail if the server does not support it
+    #[default]
+    Required,
+    /// Only encrypt the login
+    Off,
+}
+
+/// TLS options
+/// ```toml
+/// [database.tls]
+/// encryption = "required"
+/// trust_server_cert = false
+/// ca_certificate = "/etc/ssl/certs/highsteel-ca.pem"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TlsOptions {
+    /// Encryption of the connection
+    pub encryption: Encryption,
+    /// Trust the server certificate without verifying it
+    pub trust_server_cert: bool,
+    /// CA certificate (`.pem`, `.crt` or `.der`) to verify the server certificate with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_certificate: Option<PathBuf>,
+}
+
+impl DbAuth {
+    /// set the authentication method of a connection
+    pub fn apply(&self, config: &mut Config) -> DbResult<()> {
+        config.authentication(self.method()?);
+
+        Ok(())
+    }
+
+    fn method(&self) -> DbResult<AuthMethod> {
+        match self {
+            #[cfg(windows)]
+            Self::Integrated => Ok(AuthMethod::Integrated),
+            #[cfg(not(windows))]
+            Self::Integrated => Err(Error::Config(String::from("Windows integrated authentication is only supported on Windows (use `sql_login` or `kerberos`)"))),
+
+            Self::SqlLogin { user, .. } => Ok(AuthMethod::sql_server(user, self.password()?)),
+
+            #[cfg(any(windows, feature = "kerberos"))]
+            Self::Kerberos => Ok(AuthMethod::Integrated),
+            #[cfg(not(any(windows, feature = "kerberos")))]
+            Self::Kerberos => Err(Error::Config(String::from("Kerberos authentication needs `sysinteg-db` to be built with the `kerberos` feature"))),
+        }
+    }
+
+    /// password of a SQL Server login
+    fn password(&self) -> DbResult<String> {
+        match self {
+            Self::SqlLogin { password_env: Some(var), password_file: None, .. } => env::var(var)
+                .map_err(|_| Error::Config(format!("Password environment variable `{var}` is not set"))),
+            Self::SqlLogin { password_env: None, password_file: Some(path), .. } => fs::read_to_string(path)
+                // secret files usually end with a newline
+                .map(|password| password.trim_end_matches(['\r', '\n']).into())
+                .map_err(|e| Error::Config(format!("Failed to read password file `{}`: {e}", path.display()))),
+            Self::SqlLogin { .. } => Err(Error::Config(String::from("SQL login needs exactly one of `password_env` or `password_file`"))),
+            _ => Err(Error::Config(String::from("Only SQL logins have a password")))
+        }
+    }
+}
+
+impl Validate for DbAuth {
+    fn validate_into(&self, report: &mut Validation) {
+        if let Self::SqlLogin { user, password_env, password_file } = self {
+            report.required("user", user);
+
+            match (password_env, password_file) {
+                (Some(var), None) => { report.required("password_env", var); },
+                (None, Some(path)) if !path.is_file() => { report.error("password_file", format!("file `{}` does not exist", path.display())); },
+                (None, Some(_)) => (),
+                _ => { report.error("password_env", "exactly one of `password_env` or `password_file` must be set"); }
+            }
+        }
+    }
+}
+
+impl TlsOptions {
+    /// set the encryption and certificate trust of a connection
+    pub fn apply(&self, config: &mut Config) -> DbResult<()> {
+        config.encryption(match self.encryption {
+            Encryption::Required => EncryptionLevel::Required,
+            Encryption::Off => EncryptionLevel::Off,
+        });
+
+        // `trust_cert` and `trust_cert_ca` panic if both are set
+        match (&self.ca_certificate, self.trust_server_cert) {
+            (Some(_), true) => return Err(Error::Config(String::from("`trust_server_cert` and `ca_certificate` cannot both be set"))),
+            (Some(path), false) => config.trust_cert_ca(path.display()),
+            (None, true) => config.trust_cert(),
+            (None, false) => ()
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for TlsOptions {
+    fn validate_into(&self, report: &mut Validation) {
+        match &self.ca_certificate {
+            Some(_) if self.trust_server_cert => { report.error("ca_certificate", "cannot be set when `trust_server_cert` is true"); },
+            Some(path) if !path.is_file() => { report.error("ca_certificate", format!("file `{}` does not exist", path.display())); },
+            _ => ()
+        }
+    }
+}
+
+impl Default for TlsOptions {
+    fn default() -> Self {
+        Self {
+            encryption: Encryption::Required,
+            // servers use self-signed certificates, unless configured otherwise
+            trust_server_cert: true,
+            ca_certificate: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_login() {
+        let auth: DbAuth = toml::from_str("method = \"sql_login\"\nuser = \"reader\"\npassword_env = \"SYSINTEG_TEST_DB_PASSWORD\"").unwrap();
+
+        env::set_var("SYSINTEG_TEST_DB_PASSWORD", "hunter2");
+        assert_eq!(auth.password().unwrap(), "hunter2");
+        assert!(auth.validate().is_ok());
+    }
+
+    #[test]
+    fn test_password_file() {
+        let path = env::temp_dir().join("sysinteg_test_db_password");
+        fs::write(&path, "hunter2\n").unwrap();
+
+        let auth = DbAuth::SqlLogin { user: String::from("reader"), password_env: None, password_file: Some(path) };
+        assert_eq!(auth.password().unwrap(), "hunter2");
+    }
+
+    #[test]
+    fn test_invalid_sql_login() {
+        let auth = DbAuth::SqlLogin { user: String::new(), password_env: None, password_file: None };
+
+        assert!(auth.password().is_err());
+
+        let paths: Vec<_> = auth.validate().unwrap_err().0.into_iter().map(|e| e.path).collect();
+        assert_eq!(paths, ["user", "password_env"]);
+    }
+
+    #[test]
+    fn test_tls() {
+        assert_eq!(toml::from_str::<TlsOptions>("").unwrap(), TlsOptions::default());
+
+        let tls: TlsOptions = toml::from_str("encryption = \"off\"\ntrust_server_cert = true\nca_certificate = \"ca.pem\"").unwrap();
+        assert_eq!(tls.encryption, Encryption::Off);
+        assert!(tls.apply(&mut Config::new()).is_err());
+        assert_eq!(tls.validate().unwrap_err().0[0].path, "ca_certificate");
+    }
+}
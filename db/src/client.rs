@@ -2,58 +2,217 @@
 //! Database client
 
 use serde::{Deserialize, Serialize};
-use tiberius::{AuthMethod, Client, Config, error::Error};
+use std::future::Future;
+use std::time::Duration;
+use tiberius::{Client, Config, SqlBrowser};
 use tokio::net::TcpStream;
 use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
 
+use sysinteg_core::config::{Validate, Validation};
+
+use crate::{DbAuth, DbResult, Error, PoolOptions, RetryPolicy, TlsOptions};
+
 /// Client type for SQL Server database
 pub type DbClient = Client<Compat<TcpStream>>;
-/// Result type for SQL Server database
-pub type DbResult<T> = Result<T, Error>;
-
-/// Parameters for a SQL Server connection
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct DbConnParams {
-    /// Server name
-    pub server: String,
-    
-    /// Database name
-    pub database: String,
+
+sysinteg_core::documented_config! {
+    /// Parameters for a SQL Server connection
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    pub struct DbConnParams {
+        /// Server name
+        pub server: String,
+
+        /// Database name
+        pub database: String,
+
+        /// Port (defaults to 1433, or the SQL Browser port with `instance`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub port: Option<u16>,
+
+        /// Named instance, which is resolved with the SQL Browser service
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub instance: Option<String>,
+
+        /// Application name (shown by `APP_NAME()` and in the server's activity monitor)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub application_name: Option<String>,
+
+        /// Seconds to wait for a connection
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub connect_timeout: Option<u64>,
+
+        /// Seconds to wait for each query
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub command_timeout: Option<u64>,
+
+        /// Connect with `ApplicationIntent=ReadOnly`, so an Always-On listener routes the connection to a read replica
+        #[serde(default)]
+        pub read_only: bool,
+
+        /// Authentication method (`integrated`, `sql_login` or `kerberos`)
+        #[serde(default)]
+        pub auth: DbAuth,
+
+        /// Encryption and server certificate trust
+        #[serde(default)]
+        pub tls: TlsOptions,
+
+        /// Connection pool size and health checks (see [`DbPool`](crate::DbPool))
+        #[serde(default)]
+        pub pool: PoolOptions,
+
+        /// Retries of transient failures (see [`RetryPolicy`])
+        #[serde(default)]
+        pub retry: RetryPolicy,
+    }
 }
 
 impl DbConnParams {
-    /// connect to the database using the configuration
+    /// connect to the database using the configuration, retrying transient failures
     pub async fn connect(&self) -> DbResult<DbClient> {
-        connect(&self.server, &self.database).await
+        let config = self.config()?;
+        let what = format!("Connecting to {}", self.server);
+
+        self.retry.run(&what, || async {
+            match self.connect_timeout {
+                Some(secs) => tokio::time::timeout(Duration::from_secs(secs), connect_config(config.clone())).await
+                    .map_err(|_| Error::Timeout(Duration::from_secs(secs)))?,
+                None => connect_config(config.clone()).await
+            }
+        }).await
+    }
+
+    /// run a query, failing with [`Error::Timeout`] if it takes longer than `command_timeout`
+    pub async fn timeout<T, F>(&self, query: F) -> DbResult<T>
+        where F: Future<Output = tiberius::Result<T>>
+    {
+        match self.command_timeout {
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs), query).await
+                .map_err(|_| Error::Timeout(Duration::from_secs(secs)))?
+                .map_err(Into::into),
+            None => query.await.map_err(Into::into)
+        }
+    }
+
+    fn config(&self) -> DbResult<Config> {
+        let mut config = Config::new();
+
+        config.host(&self.server);
+        config.database(&self.database);
+
+        if let Some(port) = self.port {
+            config.port(port);
+        }
+
+        if let Some(instance) = &self.instance {
+            config.instance_name(instance);
+        }
+
+        if let Some(name) = &self.application_name {
+            config.application_name(name);
+        }
+
+        config.readonly(self.read_only);
+
+        self.auth.apply(&mut config)?;
+        self.tls.apply(&mut config)?;
+
+        Ok(config)
     }
 }
 
 impl sysinteg_core::config::TomlConfig for DbConnParams {}
 
+impl Validate for DbConnParams {
+    fn validate_into(&self, report: &mut Validation) {
+        report
+            .required("server", &self.server)
+            .required("database", &self.database)
+            .nested("auth", &self.auth)
+            .nested("tls", &self.tls)
+            .nested("pool", &self.pool)
+            .nested("retry", &self.retry);
+
+        if let Some(instance) = &self.instance {
+            report.required("instance", instance);
+        }
+
+        for (name, timeout) in [("connect_timeout", self.connect_timeout), ("command_timeout", self.command_timeout)] {
+            if timeout == Some(0) {
+                report.error(name, "must be greater than 0 seconds");
+            }
+        }
+    }
+}
+
 impl Default for DbConnParams {
     fn default() -> Self {
         Self {
             server: String::from("<server>"),
-            database: String::from("<database>")
+            database: String::from("<database>"),
+            port: None,
+            instance: None,
+            application_name: None,
+            connect_timeout: None,
+            command_timeout: None,
+            read_only: false,
+            auth: DbAuth::default(),
+            tls: TlsOptions::default(),
+            pool: PoolOptions::default(),
+            retry: RetryPolicy::default(),
         }
     }
 }
 
-/// configure database connection
-pub async fn connect(host: &str, database: &str) -> Result<DbClient, Error> {
-    let mut config = Config::new();
-
-    config.host(host);
-    config.database(database);
+/// configure database connection, with the default authentication and TLS options
+pub async fn connect(host: &str, database: &str) -> DbResult<DbClient> {
+    let params = DbConnParams { server: host.into(), database: database.into(), ..Default::default() };
 
-    // use windows authentication
-    config.authentication(AuthMethod::Integrated);
-    config.trust_cert();
+    params.connect().await
+}
 
-    let tcp = TcpStream::connect(config.get_addr()).await?;
+/// connect, following the redirect to a read replica (or another node) if the server sends one
+async fn connect_config(mut config: Config) -> DbResult<DbClient> {
+    // resolves the port of a named instance, if there is one
+    let tcp = TcpStream::connect_named(&config).await?;
     tcp.set_nodelay(true)?;
 
-    let client = Client::connect(config, tcp.compat_write()).await?;
+    match Client::connect(config.clone(), tcp.compat_write()).await {
+        Ok(client) => Ok(client),
+        Err(tiberius::error::Error::Routing { host, port }) => {
+            config.host(host);
+            config.port(port);
+
+            let tcp = TcpStream::connect(config.get_addr()).await?;
+            tcp.set_nodelay(true)?;
+
+            Ok(Client::connect(config, tcp.compat_write()).await?)
+        },
+        Err(e) => Err(e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(client)
+    #[test]
+    fn test_options() {
+        let params: DbConnParams = toml::from_str(r#"
+            server = "sndb-listener"
+            database = "SNDB"
+            instance = "SIGMANEST"
+            command_timeout = 30
+            read_only = true
+        "#).unwrap();
+
+        assert_eq!(params.instance.as_deref(), Some("SIGMANEST"));
+        assert_eq!(params.port, None);
+        assert!(params.read_only);
+        assert_eq!(params.auth, DbAuth::Integrated);
+        assert!(params.validate().is_ok());
+
+        let params = DbConnParams { connect_timeout: Some(0), ..params };
+        assert_eq!(params.validate().unwrap_err().0[0].path, "connect_timeout");
+    }
 }
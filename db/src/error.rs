@@ -0,0 +1,123 @@
+
+//! Error types
+
+use std::fmt::{self, Display, Formatter};
+
+use sysinteg_core::api;
+
+/// Result type for SQL Server database
+pub type DbResult<T> = Result<T, Error>;
+
+/// Errors for sysinteg-db
+#[derive(Debug)]
+pub enum Error {
+    /// SQL Server (tiberius) error
+    Tiberius(tiberius::error::Error),
+    /// I/O error
+    Io(std::io::Error),
+    /// Invalid domain value (see [`sysinteg_core::Error`])
+    Core(sysinteg_core::Error),
+    /// Column is not in the row
+    MissingColumn(String),
+    /// Column is NULL, but a value is required
+    NullValue(String),
+    /// Column type is not supported for conversion
+    UnsupportedType(String),
+    /// Column could not be converted (wraps the error with the column name)
+    InvalidColumn {
+        /// Column name
+        column: String,
+        /// Conversion error
+        error: Box<Error>
+    },
+    /// Column format cannot be used (i.e. an invalid date format)
+    Format(String),
+    /// Column has a value that is not expected
+    UnexpectedValue {
+        /// Column name
+        column: String,
+        /// Value of the column
+        value: String
+    },
+    /// Connection config cannot be used (i.e. a missing password)
+    Config(String),
+    /// Connection or query took longer than its timeout
+    Timeout(std::time::Duration),
+    /// Fixture of a [`MemoryStore`](crate::MemoryStore) cannot be read
+    Fixture(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tiberius(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Core(e) => write!(f, "{e}"),
+            Self::MissingColumn(column) => write!(f, "Column `{column}` is not in the row"),
+            Self::NullValue(column) => write!(f, "Column `{column}` is NULL"),
+            Self::UnsupportedType(ty) => write!(f, "Conversion of column type `{ty}` is not supported"),
+            Self::InvalidColumn { column, error } => write!(f, "Column `{column}`: {error}"),
+            Self::Format(message) => write!(f, "Invalid column format: {message}"),
+            Self::UnexpectedValue { column, value } => write!(f, "Column `{column}` has unexpected value `{value}`"),
+            Self::Config(message) => write!(f, "Invalid connection config: {message}"),
+            Self::Timeout(timeout) => write!(f, "Timed out after {} seconds", timeout.as_secs()),
+            Self::Fixture(message) => write!(f, "Invalid fixture: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Tiberius(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Core(e) => Some(e),
+            Self::InvalidColumn { error, .. } => Some(error.as_ref()),
+            _ => None
+        }
+    }
+}
+
+impl From<tiberius::error::Error> for Error {
+    fn from(value: tiberius::error::Error) -> Self {
+        Self::Tiberius(value)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<sysinteg_core::Error> for Error {
+    fn from(value: sysinteg_core::Error) -> Self {
+        Self::Core(value)
+    }
+}
+
+macro_rules! impl_from_core_error {
+    ($($err:ty),+ $(,)?) => {
+        $(
+            impl From<$err> for Error {
+                fn from(value: $err) -> Self {
+                    Self::Core(value.into())
+                }
+            }
+        )+
+    };
+}
+
+impl_from_core_error!(
+    api::JobShipmentParseError,
+    api::MaterialMasterParseError,
+    api::PartParseError,
+    api::PlantParseError,
+    api::ProgramNameParseError,
+    api::SheetNameParseError,
+    api::UnitOfMeasureParseError,
+    api::UomConversionError,
+    api::WbsParseError,
+    api::WbsConversionError,
+    api::ClassifyError,
+);
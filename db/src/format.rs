@@ -0,0 +1,188 @@
+
+//! Formatting column values as strings
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Write};
+use tiberius::{ColumnData, FromSql};
+
+use sysinteg_core::config::{Validate, Validation};
+
+use crate::{DbResult, Error};
+
+/// How column values are converted to strings
+///
+/// Dates and times use [`chrono` format strings](https://docs.rs/chrono/latest/chrono/format/strftime/index.html).
+/// ```toml
+/// [format]
+/// datetime = "%Y-%m-%d %H:%M:%S"
+/// decimals = 3
+/// null = ""
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ColumnFormat {
+    /// Format of `datetime`, `datetime2` and `smalldatetime` columns
+    pub datetime: String,
+    /// Format of `datetimeoffset` columns
+    pub datetime_offset: String,
+    /// Format of `date` columns
+    pub date: String,
+    /// Format of `time` columns
+    pub time: String,
+    /// Digits after the decimal point of `float`, `real` and `decimal` columns (all digits if not set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<usize>,
+    /// Value of a `bit` column that is set
+    pub true_value: String,
+    /// Value of a `bit` column that is not set
+    pub false_value: String,
+    /// Value of a NULL column (NULL is an error if not set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub null: Option<String>,
+}
+
+impl ColumnFormat {
+    /// convert a column to a string, regardless of datatype
+    ///
+    /// Errors if the column is NULL and there is no `null` value, or if a date format is invalid.
+    /// The column name of a [`Error::NullValue`] is left empty, see [`Record::into_strings`](crate::Record::into_strings).
+    pub fn format(&self, column: &ColumnData<'static>) -> DbResult<String> {
+        use ColumnData::*;
+        let value = match column {
+            U8(n)  => n.map(|n| n.to_string()),
+            I16(n) => n.map(|n| n.to_string()),
+            I32(n) => n.map(|n| n.to_string()),
+            I64(n) => n.map(|n| n.to_string()),
+            F32(n) => n.map(|n| self.decimal(n)),
+            F64(n) => n.map(|n| self.decimal(n)),
+            Numeric(n) => n.map(|n| match self.decimals {
+                Some(_) => self.decimal(f64::from(n)),
+                None => n.to_string()
+            }),
+
+            Bit(b) => b.map(|b| match b {
+                true  => self.true_value.clone(),
+                false => self.false_value.clone(),
+            }),
+
+            String(s) => s.as_ref().map(|s| s.to_string()),
+            Guid(g) => g.map(|g| g.to_string()),
+            Xml(x) => x.as_ref().map(|x| x.to_string()),
+            // hex, as SQL Server displays it
+            Binary(b) => b.as_ref().map(|b| b.iter().fold(std::string::String::from("0x"), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02X}");
+
+                hex
+            })),
+
+            DateTime(_) | SmallDateTime(_) | DateTime2(_) => NaiveDateTime::from_sql(column)?
+                .map(|dt| write_formatted(dt.format(&self.datetime), &self.datetime))
+                .transpose()?,
+            DateTimeOffset(_) => chrono::DateTime::<FixedOffset>::from_sql(column)?
+                .map(|dt| write_formatted(dt.format(&self.datetime_offset), &self.datetime_offset))
+                .transpose()?,
+            Date(_) => NaiveDate::from_sql(column)?
+                .map(|d| write_formatted(d.format(&self.date), &self.date))
+                .transpose()?,
+            Time(_) => NaiveTime::from_sql(column)?
+                .map(|t| write_formatted(t.format(&self.time), &self.time))
+                .transpose()?,
+        };
+
+        value
+            .or_else(|| self.null.clone())
+            .ok_or_else(|| Error::NullValue(std::string::String::new()))
+    }
+
+    fn decimal<N: Display>(&self, n: N) -> String {
+        match self.decimals {
+            Some(decimals) => format!("{n:.decimals$}"),
+            None => n.to_string()
+        }
+    }
+}
+
+/// write a date or time without panicking on an invalid format
+fn write_formatted(value: impl Display, format: &str) -> DbResult<String> {
+    let mut result = String::new();
+    write!(result, "{value}")
+        .map_err(|_| Error::Format(format!("`{format}` is not a valid date or time format")))?;
+
+    Ok(result)
+}
+
+impl Validate for ColumnFormat {
+    fn validate_into(&self, report: &mut Validation) {
+        for (name, format) in [("datetime", &self.datetime), ("datetime_offset", &self.datetime_offset), ("date", &self.date), ("time", &self.time)] {
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                report.error(name, format!("`{format}` is not a valid date or time format"));
+            }
+        }
+    }
+}
+
+impl Default for ColumnFormat {
+    fn default() -> Self {
+        Self {
+            datetime: String::from("%Y-%m-%d %H:%M:%S"),
+            datetime_offset: String::from("%Y-%m-%d %H:%M:%S %:z"),
+            date: String::from("%Y-%m-%d"),
+            time: String::from("%H:%M:%S"),
+            decimals: None,
+            true_value: String::from("1"),
+            false_value: String::from("0"),
+            null: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use tiberius::IntoSql;
+
+    #[test]
+    fn test_default() {
+        let format = ColumnFormat::default();
+        let datetime: NaiveDateTime = "2024-01-31T14:30:00".parse().unwrap();
+
+        assert_eq!(format.format(&ColumnData::I32(Some(42))).unwrap(), "42");
+        assert_eq!(format.format(&ColumnData::F64(Some(0.5))).unwrap(), "0.5");
+        assert_eq!(format.format(&ColumnData::Bit(Some(true))).unwrap(), "1");
+        assert_eq!(format.format(&ColumnData::Binary(Some(Cow::Borrowed(&[0x0a, 0xff])))).unwrap(), "0x0AFF");
+        assert_eq!(format.format(&datetime.into_sql()).unwrap(), "2024-01-31 14:30:00");
+        assert_eq!(format.format(&datetime.date().into_sql()).unwrap(), "2024-01-31");
+        assert_eq!(format.format(&datetime.time().into_sql()).unwrap(), "14:30:00");
+        assert!(matches!(format.format(&ColumnData::String(None)), Err(Error::NullValue(_))));
+    }
+
+    #[test]
+    fn test_configured() {
+        let format: ColumnFormat = toml::from_str(r#"
+            datetime = "%d/%m/%Y %H:%M"
+            decimals = 3
+            true_value = "X"
+            false_value = ""
+            null = ""
+        "#).unwrap();
+        let datetime: NaiveDateTime = "2024-01-31T14:30:00".parse().unwrap();
+
+        assert_eq!(format.format(&datetime.into_sql()).unwrap(), "31/01/2024 14:30");
+        assert_eq!(format.format(&ColumnData::F64(Some(0.5))).unwrap(), "0.500");
+        assert_eq!(format.format(&ColumnData::Numeric(Some(tiberius::numeric::Numeric::new_with_scale(12345, 2)))).unwrap(), "123.450");
+        assert_eq!(format.format(&ColumnData::Bit(Some(true))).unwrap(), "X");
+        assert_eq!(format.format(&ColumnData::I32(None)).unwrap(), "");
+    }
+
+    #[test]
+    fn test_invalid_format() {
+        let format = ColumnFormat { date: String::from("%Q"), ..Default::default() };
+        let date: NaiveDate = "2024-01-31".parse().unwrap();
+
+        assert!(matches!(format.format(&date.into_sql()), Err(Error::Format(_))));
+        assert_eq!(format.validate().unwrap_err().0[0].path, "date");
+    }
+}
@@ -1,8 +1,20 @@
 
 //! database connections
 
+mod auth;
 mod client;
+mod error;
+mod format;
+mod pool;
+mod retry;
+mod store;
 mod utils;
 
-pub use client::{DbClient, DbConnParams, DbResult, connect};
+pub use auth::{DbAuth, Encryption, TlsOptions};
+pub use client::{DbClient, DbConnParams, connect};
+pub use error::{DbResult, Error};
+pub use format::ColumnFormat;
+pub use pool::{DbPool, PoolOptions, PooledClient};
+pub use retry::{RetryPolicy, Transient};
+pub use store::{LogEntry, MemoryStore, Record, SapDataset, SigmanestStore, StatusQuery};
 pub use utils::*;
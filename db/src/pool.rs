@@ -0,0 +1,233 @@
+
+//! Connection pool
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use sysinteg_core::config::{Validate, Validation};
+
+use crate::{DbClient, DbConnParams, DbResult, Error, Transient};
+
+/// seconds a health check can take if neither `connect_timeout` nor `command_timeout` is set
+const HEALTH_CHECK_TIMEOUT: u64 = 5;
+
+/// Connection pool options
+/// ```toml
+/// [database.pool]
+/// max_size = 4
+/// idle_timeout = 300
+/// health_check = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PoolOptions {
+    /// Maximum number of open connections
+    pub max_size: usize,
+    /// Seconds an unused connection is kept open
+    pub idle_timeout: u64,
+    /// Check that an idle connection still works before it is used
+    pub health_check: bool,
+}
+
+/// Pool of database connections, which can be shared (cloned) between tasks
+///
+/// Connections are opened as they are needed, up to [`PoolOptions::max_size`].
+/// Connections that are closed or broken are replaced when they are next checked out.
+#[derive(Debug, Clone)]
+pub struct DbPool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    params: DbConnParams,
+    permits: Arc<Semaphore>,
+    idle: Mutex<Vec<IdleClient>>,
+}
+
+#[derive(Debug)]
+struct IdleClient {
+    client: DbClient,
+    since: Instant,
+}
+
+/// Connection checked out of a [`DbPool`], which goes back to the pool when it is dropped
+#[derive(Debug)]
+pub struct PooledClient {
+    client: Option<DbClient>,
+    pool: Arc<PoolInner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl DbPool {
+    /// pool for a connection config (no connections are opened until they are needed)
+    pub fn new(params: DbConnParams) -> Self {
+        let permits = Arc::new(Semaphore::new(params.pool.max_size.max(1)));
+
+        Self { inner: Arc::new(PoolInner { params, permits, idle: Mutex::new(Vec::new()) }) }
+    }
+
+    /// check out a connection, waiting if all connections are in use
+    pub async fn get(&self) -> DbResult<PooledClient> {
+        // unwrap is safe here because the semaphore is never closed
+        let permit = self.inner.permits.clone().acquire_owned().await.unwrap();
+        let options = &self.inner.params.pool;
+
+        while let Some(idle) = self.inner.take_idle() {
+            if idle.since.elapsed() > Duration::from_secs(options.idle_timeout) {
+                continue;
+            }
+
+            let mut client = idle.client;
+            if options.health_check && !is_healthy(&mut client, &self.inner.params).await {
+                continue;
+            }
+
+            return Ok(PooledClient { client: Some(client), pool: self.inner.clone(), _permit: permit });
+        }
+
+        let client = self.inner.params.connect().await?;
+
+        Ok(PooledClient { client: Some(client), pool: self.inner.clone(), _permit: permit })
+    }
+
+    /// run `f` with a connection, retrying transient failures on a new connection (see [`RetryPolicy`](crate::RetryPolicy))
+    ///
+    /// `what` describes the operation in the log message of each retry.
+    /// The connection is discarded after a transient failure, since it may be broken.
+    pub async fn retry<T, E, F>(&self, what: &str, mut f: F) -> Result<T, E>
+        where
+            E: From<Error> + Transient + Display,
+            F: AsyncFnMut(&mut PooledClient) -> Result<T, E>
+    {
+        let policy = &self.inner.params.retry;
+
+        let mut attempt = 1;
+        loop {
+            let result = match self.get().await {
+                Ok(mut client) => {
+                    let result = f(&mut client).await;
+                    if matches!(&result, Err(e) if e.is_transient()) {
+                        client.discard();
+                    }
+
+                    result
+                },
+                Err(e) => Err(e.into())
+            };
+
+            match result {
+                Err(e) if policy.should_retry(&e, attempt) => {
+                    policy.backoff(what, attempt, &e).await;
+                    attempt += 1;
+                },
+                result => return result
+            }
+        }
+    }
+
+    /// connection config of the pool
+    pub fn params(&self) -> &DbConnParams {
+        &self.inner.params
+    }
+
+    /// number of open connections that are not checked out
+    pub fn idle(&self) -> usize {
+        self.inner.idle.lock().map(|idle| idle.len()).unwrap_or(0)
+    }
+}
+
+impl PoolInner {
+    fn take_idle(&self) -> Option<IdleClient> {
+        self.idle.lock().ok()?.pop()
+    }
+}
+
+impl PooledClient {
+    /// close the connection instead of returning it to the pool (i.e. after an I/O error)
+    pub fn discard(mut self) {
+        self.client = None;
+    }
+}
+
+impl Deref for PooledClient {
+    type Target = DbClient;
+
+    fn deref(&self) -> &Self::Target {
+        // unwrap is safe here because the client is only taken when the connection is dropped
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // unwrap is safe here because the client is only taken when the connection is dropped
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let (Some(client), Ok(mut idle)) = (self.client.take(), self.pool.idle.lock()) {
+            idle.push(IdleClient { client, since: Instant::now() });
+        }
+    }
+}
+
+impl Validate for PoolOptions {
+    fn validate_into(&self, report: &mut Validation) {
+        if self.max_size == 0 {
+            report.error("max_size", "must be at least 1");
+        }
+    }
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 4,
+            idle_timeout: 300,
+            health_check: true,
+        }
+    }
+}
+
+/// if a connection still works, which is limited to `connect_timeout` (or `command_timeout`) so that a dead connection does not hang
+async fn is_healthy(client: &mut DbClient, params: &DbConnParams) -> bool {
+    let secs = params.connect_timeout
+        .or(params.command_timeout)
+        .unwrap_or(HEALTH_CHECK_TIMEOUT);
+
+    let check = async {
+        client.simple_query("SELECT 1").await?
+            .into_results().await
+    };
+
+    matches!(tokio::time::timeout(Duration::from_secs(secs), check).await, Ok(Ok(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options() {
+        assert_eq!(toml::from_str::<PoolOptions>("").unwrap(), PoolOptions::default());
+
+        let options: PoolOptions = toml::from_str("max_size = 0").unwrap();
+        assert_eq!(options.validate().unwrap_err().0[0].path, "max_size");
+    }
+
+    #[test]
+    fn test_lazy() {
+        let pool = DbPool::new(DbConnParams::default());
+
+        // no connections are opened until they are needed
+        assert_eq!(pool.idle(), 0);
+        assert_eq!(pool.inner.permits.available_permits(), 4);
+    }
+}
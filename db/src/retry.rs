@@ -0,0 +1,238 @@
+
+//! Retrying transient database failures
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::fmt::Display;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime};
+
+use sysinteg_core::config::{Validate, Validation};
+
+use crate::Error;
+
+/// Server error codes that are safe to retry
+///
+/// - 1205: chosen as a deadlock victim
+/// - 40197, 40501, 40613: service busy or unavailable (failover)
+const TRANSIENT_SERVER_ERRORS: [u32; 4] = [1205, 40197, 40501, 40613];
+
+/// How transient failures (network, timeouts, deadlocks and failovers) are retried
+///
+/// The delay before each retry doubles, up to `max_delay`.
+/// With `jitter`, a random part of the delay (up to half) is removed, so clients do not all retry at once.
+/// ```toml
+/// [database.retry]
+/// max_attempts = 3
+/// initial_delay = 500
+/// max_delay = 30000
+/// jitter = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first (1 disables retries)
+    pub max_attempts: u32,
+    /// Milliseconds to wait before the first retry
+    pub initial_delay: u64,
+    /// Maximum milliseconds to wait between retries
+    pub max_delay: u64,
+    /// Randomize the delays
+    pub jitter: bool,
+}
+
+/// Errors that may succeed if they are retried
+pub trait Transient {
+    /// if the error is transient (i.e. a dropped connection), rather than a SQL or logic error
+    fn is_transient(&self) -> bool;
+}
+
+impl RetryPolicy {
+    /// run `f` until it succeeds, fails with an error that is not transient, or runs out of attempts
+    ///
+    /// `what` describes the operation in the log message of each retry
+    pub async fn run<T, E, F, Fut>(&self, what: &str, mut f: F) -> Result<T, E>
+        where
+            E: Transient + Display,
+            F: FnMut() -> Fut,
+            Fut: Future<Output = Result<T, E>>
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(e) if self.should_retry(&e, attempt) => {
+                    self.backoff(what, attempt, &e).await;
+                    attempt += 1;
+                },
+                result => return result
+            }
+        }
+    }
+
+    pub(crate) fn should_retry<E: Transient>(&self, error: &E, attempt: u32) -> bool {
+        error.is_transient() && attempt < self.max_attempts
+    }
+
+    /// log the failure and wait before the next attempt
+    pub(crate) async fn backoff<E: Display>(&self, what: &str, attempt: u32, error: &E) {
+        let delay = self.delay(attempt);
+        log::warn!("{what} failed (attempt {attempt} of {}), retrying in {delay:?}: {error}", self.max_attempts);
+
+        tokio::time::sleep(delay).await;
+    }
+
+    /// delay before retry number `attempt` (starting at 1)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.initial_delay.saturating_mul(factor).min(self.max_delay);
+
+        let delay = match self.jitter {
+            true  => delay - (delay as f64 * random_fraction() / 2.0) as u64,
+            false => delay,
+        };
+
+        Duration::from_millis(delay)
+    }
+}
+
+impl Transient for tiberius::error::Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Io { .. } => true,
+            Self::Server(e) => TRANSIENT_SERVER_ERRORS.contains(&e.code()),
+            _ => false
+        }
+    }
+}
+
+impl Transient for Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Tiberius(e) => e.is_transient(),
+            Self::Io(_) | Self::Timeout(_) => true,
+            _ => false
+        }
+    }
+}
+
+impl Transient for anyhow::Error {
+    fn is_transient(&self) -> bool {
+        if let Some(e) = self.downcast_ref::<Error>() {
+            e.is_transient()
+        } else if let Some(e) = self.downcast_ref::<tiberius::error::Error>() {
+            e.is_transient()
+        } else {
+            self.is::<std::io::Error>()
+        }
+    }
+}
+
+impl Validate for RetryPolicy {
+    fn validate_into(&self, report: &mut Validation) {
+        if self.max_attempts == 0 {
+            report.error("max_attempts", "must be at least 1");
+        }
+
+        if self.max_delay < self.initial_delay {
+            report.error("max_delay", "must be at least `initial_delay`");
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: 500,
+            max_delay: 30_000,
+            jitter: true,
+        }
+    }
+}
+
+/// random number in `[0, 1)`, which is good enough for jitter
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn io_error() -> Error {
+        Error::Io(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset"))
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy { initial_delay: 1, max_delay: 2, ..Default::default() }
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy { jitter: false, ..Default::default() };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(3), Duration::from_millis(2000));
+        assert_eq!(policy.delay(10), Duration::from_millis(30_000));
+        assert_eq!(policy.delay(100), Duration::from_millis(30_000));
+
+        let policy = RetryPolicy::default();
+        for attempt in 1..5 {
+            let delay = policy.delay(attempt);
+            assert!(delay <= Duration::from_millis(500 << (attempt - 1)) && delay >= Duration::from_millis(250 << (attempt - 1)));
+        }
+    }
+
+    #[test]
+    fn test_transient() {
+        assert!(io_error().is_transient());
+        assert!(Error::Timeout(Duration::from_secs(1)).is_transient());
+        assert!(!Error::MissingColumn(String::from("Job")).is_transient());
+        assert!(!tiberius::error::Error::Conversion("bad value".into()).is_transient());
+
+        assert!(anyhow::Error::from(io_error()).is_transient());
+        assert!(!anyhow::anyhow!("no such file").is_transient());
+    }
+
+    #[tokio::test]
+    async fn test_retries() {
+        let attempts = Cell::new(0);
+        let result = policy().run("test", || async {
+            attempts.set(attempts.get() + 1);
+
+            match attempts.get() {
+                1 | 2 => Err(io_error()),
+                n => Ok(n)
+            }
+        }).await;
+
+        assert_eq!(result.unwrap(), 3);
+
+        // only the maximum attempts are made
+        let attempts = Cell::new(0);
+        let result: Result<(), _> = policy().run("test", || async { attempts.set(attempts.get() + 1); Err(io_error()) }).await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry() {
+        let attempts = Cell::new(0);
+        let result: Result<(), _> = policy().run("test", || async {
+            attempts.set(attempts.get() + 1);
+
+            Err(Error::UnsupportedType(String::from("Xml")))
+        }).await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+}
@@ -0,0 +1,183 @@
+
+//! In-memory store, loaded from a fixture
+
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use super::{LogEntry, Record, SapDataset, SigmanestStore, StatusQuery};
+use crate::{DbResult, Error};
+
+/// Store that keeps its data in memory, for testing without a SQL Server
+///
+/// Data is loaded from a TOML or JSON fixture. Clones share the same runtimes and log.
+/// ```toml
+/// [runtime]
+/// SapProductionData = "2024-01-31T14:00:00"
+///
+/// [[status]]
+/// ProgramName = "52198"
+/// Status = "Active"
+/// Timestamp = "2024-01-31T14:30:00"
+/// SheetName = "S12345"
+///
+/// [[production]]
+/// MachineName = "Plant_3_Gemini"
+/// TotalNestedArea = 1440.0
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    data: Arc<Fixture>,
+    runtime: Arc<Mutex<HashMap<String, NaiveDateTime>>>,
+    log: Arc<Mutex<Vec<LogEntry>>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Fixture {
+    /// rows returned by status queries, which are matched by the column of the query (see [`StatusQuery::column`])
+    status: Vec<Record>,
+    production: Vec<Record>,
+    issue: Vec<Record>,
+    /// last runtime of each dataset, by its name in `HighSteel.RuntimeInfo`
+    runtime: HashMap<String, NaiveDateTime>,
+}
+
+impl MemoryStore {
+    /// load a store from a TOML fixture
+    pub fn from_toml(fixture: &str) -> DbResult<Self> {
+        toml::from_str(fixture)
+            .map(Self::new)
+            .map_err(|e| Error::Fixture(e.to_string()))
+    }
+
+    /// load a store from a JSON fixture
+    pub fn from_json(fixture: &str) -> DbResult<Self> {
+        serde_json::from_str(fixture)
+            .map(Self::new)
+            .map_err(|e| Error::Fixture(e.to_string()))
+    }
+
+    /// load a store from a fixture file (`.json` files are JSON, and anything else is TOML)
+    pub fn load(path: impl AsRef<Path>) -> DbResult<Self> {
+        let path = path.as_ref();
+        let fixture = fs::read_to_string(path)?;
+
+        match path.extension() {
+            Some(ext) if ext == "json" => Self::from_json(&fixture),
+            _ => Self::from_toml(&fixture)
+        }
+    }
+
+    fn new(fixture: Fixture) -> Self {
+        let runtime = Arc::new(Mutex::new(fixture.runtime.clone()));
+
+        Self { data: Arc::new(fixture), runtime, log: Default::default() }
+    }
+
+    /// messages that have been logged
+    pub fn logged(&self) -> Vec<LogEntry> {
+        self.log.lock().map(|log| log.clone()).unwrap_or_default()
+    }
+}
+
+impl SigmanestStore for MemoryStore {
+    async fn status(&self, query: &StatusQuery) -> DbResult<Option<Record>> {
+        let value = query.value();
+
+        let record = self.data.status.iter()
+            .find(|record| matches!(record.optional::<&str>(query.column()), Ok(Some(v)) if v.eq_ignore_ascii_case(&value)));
+
+        Ok(record.cloned())
+    }
+
+    async fn dataset(&self, dataset: SapDataset, _end: NaiveDateTime) -> DbResult<Vec<Record>> {
+        match dataset {
+            SapDataset::Production => Ok(self.data.production.clone()),
+            SapDataset::Issue => Ok(self.data.issue.clone()),
+        }
+    }
+
+    async fn last_runtime(&self, dataset: SapDataset) -> DbResult<Option<NaiveDateTime>> {
+        Ok(self.runtime.lock().ok().and_then(|runtime| runtime.get(&dataset.runtime_name()).copied()))
+    }
+
+    async fn set_last_runtime(&self, dataset: SapDataset, end: NaiveDateTime) -> DbResult<()> {
+        if let Ok(mut runtime) = self.runtime.lock() {
+            runtime.insert(dataset.runtime_name(), end);
+        }
+
+        Ok(())
+    }
+
+    async fn log(&self, entry: &LogEntry) -> DbResult<()> {
+        if let Ok(mut log) = self.log.lock() {
+            log.push(entry.clone());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"
+        [runtime]
+        SapProductionData = "2024-01-31T14:00:00"
+
+        [[status]]
+        ProgramName = "52198"
+        Status = "Active"
+
+        [[production]]
+        MachineName = "Plant_3_Gemini"
+        TotalNestedArea = 1440.0
+    "#;
+
+    fn end() -> NaiveDateTime {
+        "2024-01-31T15:00:00".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let store = MemoryStore::from_toml(FIXTURE).unwrap();
+
+        let found = store.status(&StatusQuery::Program("52198".parse().unwrap())).await.unwrap();
+        assert_eq!(found.unwrap().required::<&str>("Status").unwrap(), "Active");
+
+        let missing = store.status(&StatusQuery::Program("52199".parse().unwrap())).await.unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_runtime() {
+        let store = MemoryStore::from_toml(FIXTURE).unwrap();
+        assert_eq!(store.dataset(SapDataset::Production, end()).await.unwrap().len(), 1);
+        assert!(store.dataset(SapDataset::Issue, end()).await.unwrap().is_empty());
+
+        assert_eq!(store.last_runtime(SapDataset::Issue).await.unwrap(), None);
+        store.set_last_runtime(SapDataset::Production, end()).await.unwrap();
+
+        // clones share runtimes
+        assert_eq!(store.clone().last_runtime(SapDataset::Production).await.unwrap(), Some(end()));
+    }
+
+    #[tokio::test]
+    async fn test_json() {
+        let store = MemoryStore::from_json(r#"{ "issue": [{ "PartName": "1200123A-X1", "Qty": 2 }] }"#).unwrap();
+
+        let rows = store.dataset(SapDataset::Issue, end()).await.unwrap();
+        assert_eq!(rows[0].required::<i32>("Qty").unwrap(), 2);
+
+        let entry = LogEntry { timestamp: end(), app: String::from("test"), level: log::Level::Info, message: String::from("pulled") };
+        store.log(&entry).await.unwrap();
+        assert_eq!(store.logged(), [entry]);
+
+        assert!(matches!(MemoryStore::from_toml("status = 1"), Err(Error::Fixture(_))));
+    }
+}
@@ -0,0 +1,157 @@
+
+//! Storage of Sigmanest data, so that it can be read from SQL Server or from memory (for testing)
+
+mod memory;
+mod mssql;
+mod record;
+
+pub use memory::MemoryStore;
+pub use record::Record;
+
+use chrono::NaiveDateTime;
+use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+
+use sysinteg_core::api::{ClassifyError, Identifier, MaterialMaster, Part, ProgramName, SheetName};
+
+use crate::DbResult;
+
+/// Sigmanest data that the applications read and write
+///
+/// Implemented for SQL Server by [`DbPool`](crate::DbPool) and in memory by [`MemoryStore`].
+pub trait SigmanestStore: Send + Sync {
+    /// status of a program, part, sheet or material master (`None` if it is not found)
+    fn status(&self, query: &StatusQuery) -> impl Future<Output = DbResult<Option<Record>>> + Send;
+
+    /// rows of a SAP dataset since its last runtime, until `end`
+    fn dataset(&self, dataset: SapDataset, end: NaiveDateTime) -> impl Future<Output = DbResult<Vec<Record>>> + Send;
+
+    /// when a dataset was last pulled until, if it ever was
+    fn last_runtime(&self, dataset: SapDataset) -> impl Future<Output = DbResult<Option<NaiveDateTime>>> + Send;
+
+    /// set when a dataset was last pulled until
+    fn set_last_runtime(&self, dataset: SapDataset, end: NaiveDateTime) -> impl Future<Output = DbResult<()>> + Send;
+
+    /// write an application log message
+    fn log(&self, entry: &LogEntry) -> impl Future<Output = DbResult<()>> + Send;
+}
+
+/// Status lookup of a Sigmanest object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusQuery {
+    /// Program status
+    Program(ProgramName),
+    /// Status of the programs a part is nested on
+    Part(Part),
+    /// Sheet status
+    Sheet(SheetName),
+    /// Status of the sheets of a material master
+    Material(MaterialMaster),
+}
+
+/// Dataset that is exported to SAP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SapDataset {
+    /// Production (burned parts)
+    Production,
+    /// Issued parts
+    Issue,
+}
+
+/// Application log message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Time the message was logged
+    pub timestamp: NaiveDateTime,
+    /// Application (or module) that logged the message
+    pub app: String,
+    /// Log level
+    pub level: log::Level,
+    /// Message
+    pub message: String,
+}
+
+impl StatusQuery {
+    /// column of the status results that has the queried value
+    pub fn column(&self) -> &str {
+        match self {
+            Self::Program(_) => "ProgramName",
+            Self::Part(_) => "PartName",
+            Self::Sheet(_) => "SheetName",
+            Self::Material(_) => "MaterialMaster",
+        }
+    }
+
+    /// queried value, in its canonical form
+    pub fn value(&self) -> String {
+        match self {
+            Self::Program(program) => program.to_string(),
+            Self::Part(part) => part.to_string(),
+            Self::Sheet(sheet) => sheet.to_string(),
+            Self::Material(mm) => mm.to_string(),
+        }
+    }
+}
+
+impl Display for StatusQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Program(program) => write!(f, "program `{program}`"),
+            Self::Part(part) => write!(f, "part `{part}`"),
+            Self::Sheet(sheet) => write!(f, "sheet `{sheet}`"),
+            Self::Material(mm) => write!(f, "material master `{mm}`"),
+        }
+    }
+}
+
+impl TryFrom<Identifier> for StatusQuery {
+    type Error = String;
+
+    fn try_from(value: Identifier) -> Result<Self, Self::Error> {
+        match value {
+            Identifier::Program(program) => Ok(Self::Program(program)),
+            Identifier::Part(part) => Ok(Self::Part(part)),
+            Identifier::Sheet(sheet) => Ok(Self::Sheet(sheet)),
+            Identifier::MaterialMaster(mm) => Ok(Self::Material(mm)),
+            id => Err(format!("No query available for {} `{}`", id.kind(), id))
+        }
+    }
+}
+
+impl TryFrom<&str> for StatusQuery {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match Identifier::classify(value) {
+            Ok(id) => Self::try_from(id),
+
+            // if only one of the candidates can be queried, use that one
+            Err(ClassifyError::Ambiguous(val, candidates)) => {
+                let mut queries: Vec<Self> = candidates.iter()
+                    .filter_map(|id| Self::try_from(id.clone()).ok())
+                    .collect();
+
+                match queries.len() {
+                    1 => Ok(queries.remove(0)),
+                    _ => Err(ClassifyError::Ambiguous(val, candidates).to_string())
+                }
+            },
+            Err(e) => Err(e.to_string())
+        }
+    }
+}
+
+impl SapDataset {
+    /// name of the dataset
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Production => "Production",
+            Self::Issue => "Issue",
+        }
+    }
+
+    /// name of the dataset in `HighSteel.RuntimeInfo`
+    pub fn runtime_name(&self) -> String {
+        format!("Sap{}Data", self.name())
+    }
+}
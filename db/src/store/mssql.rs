@@ -0,0 +1,95 @@
+
+//! SQL Server store
+
+use chrono::NaiveDateTime;
+
+use super::{LogEntry, Record, SapDataset, SigmanestStore, StatusQuery};
+use crate::{DbClient, DbPool, DbResult};
+
+impl StatusQuery {
+    /// run the status procedure
+    pub async fn execute<'a>(&'a self, client: &'a mut DbClient) -> tiberius::Result<tiberius::QueryStream<'a>> {
+        match self {
+            Self::Program(program) => client.query("EXEC GetProgramStatus @ProgramName=@P1", &[program]).await,
+            Self::Part(part) => client.query("EXEC GetPartStatus @ProgramName=@P1", &[part]).await,
+            Self::Sheet(sheet) => client.query("EXEC GetSheetStatus @ProgramName=@P1", &[sheet]).await,
+            Self::Material(mm) => client.query("EXEC GetMaterialStatus @ProgramName=@P1", &[mm]).await,
+        }
+    }
+}
+
+impl SapDataset {
+    fn query(&self) -> &str {
+        match self {
+            Self::Production => "EXEC SapProductionData_SinceLastRun @End = @P1",
+            Self::Issue      => "EXEC SapIssueData_SinceLastRun @End = @P1",
+        }
+    }
+}
+
+/// reads and log entries are retried (see [`DbPool::retry`]), and each query is limited to `command_timeout`
+///
+/// the retried closures own what they use (i.e. a clone of the pool),
+///  because the future of an async closure that borrows cannot be proven `Send`
+impl SigmanestStore for DbPool {
+    async fn status(&self, query: &StatusQuery) -> DbResult<Option<Record>> {
+        let (pool, query) = (self.clone(), query.clone());
+
+        self.retry(&format!("Looking up {query}"), async move |client| {
+            let rows = pool.params().timeout(query.execute(client)).await?;
+
+            // row is read before the connection goes back to the pool
+            Ok(rows.into_row().await?.map(Record::from))
+        }).await
+    }
+
+    async fn dataset(&self, dataset: SapDataset, end: NaiveDateTime) -> DbResult<Vec<Record>> {
+        let pool = self.clone();
+
+        self.retry(&format!("Pulling {} dataset", dataset.name()), async move |client| {
+            let rows = pool.params().timeout(client.query(dataset.query(), &[&end])).await?
+                .into_first_result().await?;
+
+            Ok(rows.into_iter().map(Record::from).collect())
+        }).await
+    }
+
+    async fn last_runtime(&self, dataset: SapDataset) -> DbResult<Option<NaiveDateTime>> {
+        let (pool, name) = (self.clone(), dataset.runtime_name());
+
+        self.retry(&format!("Reading last runtime of {name}"), async move |client| {
+            let row = pool.params().timeout(client.query("SELECT last_runtime FROM HighSteel.RuntimeInfo WHERE name=@P1", &[&name])).await?
+                .into_row().await?;
+
+            match row {
+                Some(row) => Record::from(row).optional("last_runtime"),
+                None => Ok(None)
+            }
+        }).await
+    }
+
+    async fn set_last_runtime(&self, dataset: SapDataset, end: NaiveDateTime) -> DbResult<()> {
+        let (pool, name) = (self.clone(), dataset.runtime_name());
+
+        // setting the runtime again has the same result, so it can be retried
+        self.retry(&format!("Updating last runtime of {name}"), async move |client| {
+            pool.params().timeout(client.execute("UPDATE HighSteel.RuntimeInfo SET last_runtime=@P1 WHERE name=@P2", &[&end, &name])).await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn log(&self, entry: &LogEntry) -> DbResult<()> {
+        let (pool, entry) = (self.clone(), entry.clone());
+
+        // the logger stops at the first failure that is still transient after the retries
+        self.retry("Writing log entry", async move |client| {
+            pool.params().timeout(client.execute(
+                "INSERT INTO HighSteel.Log(timestamp, app, level, message) VALUES(@P1, @P2, @P3, @P4)",
+                &[&entry.timestamp, &entry.app, &entry.level.as_str(), &entry.message]
+            )).await?;
+
+            Ok(())
+        }).await
+    }
+}
@@ -0,0 +1,194 @@
+
+//! Query result rows that are not tied to a connection
+
+use chrono::NaiveDateTime;
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use tiberius::{ColumnData, FromSql, IntoSql, Row};
+
+use crate::{is_null, ColumnFormat, DbResult, Error};
+
+/// Row of a query result, which can be built from a [`tiberius::Row`] or a fixture
+///
+/// In a fixture, a row is a table of column names and values.
+/// Integers are `int` columns (or `bigint` if they are too large), decimals are `float` columns
+/// and strings in the form `2024-01-31T14:30:00` are `datetime2` columns.
+/// In JSON, `null` is a NULL column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    columns: Arc<[String]>,
+    values: Vec<ColumnData<'static>>,
+}
+
+/// value of a column in a fixture
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum FixtureValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    DateTime(NaiveDateTime),
+    String(String),
+}
+
+impl Record {
+    /// row from column names and their values
+    ///
+    /// *panics* if there is not a value for each column
+    pub fn new(columns: impl Into<Arc<[String]>>, values: Vec<ColumnData<'static>>) -> Self {
+        let columns = columns.into();
+        assert_eq!(columns.len(), values.len(), "Record must have a value for each column");
+
+        Self { columns, values }
+    }
+
+    /// column names, in order
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// value of a column, if the column is in the row
+    pub fn get(&self, column: &str) -> Option<&ColumnData<'static>> {
+        self.columns.iter()
+            .position(|name| name == column)
+            .map(|i| &self.values[i])
+    }
+
+    /// get a column value that must not be NULL
+    pub fn required<'a, R: FromSql<'a>>(&'a self, column: &str) -> DbResult<R> {
+        self.optional(column)?
+            .ok_or_else(|| Error::NullValue(column.into()))
+    }
+
+    /// get a column value that may be NULL
+    pub fn optional<'a, R: FromSql<'a>>(&'a self, column: &str) -> DbResult<Option<R>> {
+        match self.get(column) {
+            // NULL is not typed in a fixture, so it is checked before the conversion
+            Some(value) if is_null(value) => Ok(None),
+            Some(value) => Ok(R::from_sql(value)?),
+            None => Err(Error::MissingColumn(column.into()))
+        }
+    }
+
+    /// converts each column to a string, with the default [`ColumnFormat`]
+    pub fn into_strings(self) -> DbResult<Vec<String>> {
+        self.into_strings_with(&ColumnFormat::default())
+    }
+
+    /// converts each column to a string
+    ///
+    /// Errors name the column that could not be converted.
+    pub fn into_strings_with(self, format: &ColumnFormat) -> DbResult<Vec<String>> {
+        self.values.into_iter()
+            .zip(self.columns.iter())
+            .map(|(value, name)| format.format(&value)
+                .map_err(|e| match e {
+                    Error::NullValue(_) => Error::NullValue(name.clone()),
+                    e => Error::InvalidColumn { column: name.clone(), error: Box::new(e) }
+                })
+            )
+            .collect()
+    }
+}
+
+impl From<Row> for Record {
+    fn from(row: Row) -> Self {
+        let columns: Vec<String> = row.columns().iter()
+            .map(|col| col.name().to_string())
+            .collect();
+
+        Self::new(columns, row.into_iter().collect())
+    }
+}
+
+impl From<FixtureValue> for ColumnData<'static> {
+    fn from(value: FixtureValue) -> Self {
+        match value {
+            FixtureValue::Null => ColumnData::String(None),
+            FixtureValue::Bool(b) => ColumnData::Bit(Some(b)),
+            FixtureValue::Int(n) => match i32::try_from(n) {
+                Ok(n) => ColumnData::I32(Some(n)),
+                Err(_) => ColumnData::I64(Some(n))
+            },
+            FixtureValue::Float(n) => ColumnData::F64(Some(n)),
+            FixtureValue::DateTime(dt) => dt.into_sql(),
+            FixtureValue::String(s) => ColumnData::String(Some(Cow::Owned(s))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Record {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RecordVisitor;
+
+        impl<'de> Visitor<'de> for RecordVisitor {
+            type Value = Record;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a table of column names and values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut columns = Vec::new();
+                let mut values = Vec::new();
+
+                // columns are kept in the order they are in the fixture
+                while let Some((column, value)) = map.next_entry::<String, FixtureValue>()? {
+                    columns.push(column);
+                    values.push(value.into());
+                }
+
+                Ok(Record::new(columns, values))
+            }
+        }
+
+        deserializer.deserialize_map(RecordVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture() {
+        let record: Record = toml::from_str(r#"
+            ProgramName = "52198"
+            Timestamp = "2024-01-31T14:30:00"
+            Thickness = 0.5
+            Qty = 2
+        "#).unwrap();
+
+        assert_eq!(record.columns(), ["ProgramName", "Timestamp", "Thickness", "Qty"]);
+        assert_eq!(record.required::<&str>("ProgramName").unwrap(), "52198");
+        assert_eq!(record.required::<NaiveDateTime>("Timestamp").unwrap().to_string(), "2024-01-31 14:30:00");
+        assert_eq!(record.required::<f64>("Thickness").unwrap(), 0.5);
+        assert_eq!(record.required::<i32>("Qty").unwrap(), 2);
+        assert!(matches!(record.optional::<&str>("Heat"), Err(Error::MissingColumn(_))));
+    }
+
+    #[test]
+    fn test_null() {
+        let record: Record = serde_json::from_str(r#"{ "Mill": null, "Thickness": null }"#).unwrap();
+
+        assert_eq!(record.optional::<f64>("Thickness").unwrap(), None);
+        assert!(matches!(record.required::<&str>("Mill"), Err(Error::NullValue(col)) if col == "Mill"));
+        assert!(matches!(record.clone().into_strings(), Err(Error::NullValue(col)) if col == "Mill"));
+
+        let format = ColumnFormat { null: Some(String::new()), ..Default::default() };
+        assert_eq!(record.into_strings_with(&format).unwrap(), ["", ""]);
+    }
+
+    #[test]
+    fn test_invalid_column() {
+        let record: Record = toml::from_str(r#"Timestamp = "2024-01-31T14:30:00""#).unwrap();
+        let format = ColumnFormat { datetime: String::from("%Q"), ..Default::default() };
+
+        let error = record.into_strings_with(&format).unwrap_err();
+        assert!(matches!(&error, Error::InvalidColumn { column, .. } if column == "Timestamp"));
+        assert!(error.to_string().starts_with("Column `Timestamp`: "));
+    }
+}
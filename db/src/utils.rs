@@ -1,35 +1,74 @@
 
 //! Database utilities
 //! 
-use tiberius::{ColumnData, Row};
+use tiberius::{ColumnData, FromSql, Row};
 
-/// convert SQL Server column to string, regardless of datatype
-pub fn column_to_str(column: ColumnData) -> String {
-    use ColumnData::*;
-    match column {
-        U8(n)  => n.unwrap().to_string(),
-        I16(n) => n.unwrap().to_string(),
-        I32(n) => n.unwrap().to_string(),
-        I64(n) => n.unwrap().to_string(),
-        F32(n) => n.unwrap().to_string(),
-        F64(n) => n.unwrap().to_string(),
-        Numeric(n) => n.unwrap().to_string(),
+use crate::{ColumnFormat, DbResult, Error, Record};
 
-        String(s) => s.unwrap().to_string(),
+/// convert SQL Server column to string, regardless of datatype, with the default [`ColumnFormat`]
+///
+/// Errors if the column is NULL.
+/// The column name of a [`Error::NullValue`] is left empty, see [`row_to_string`].
+pub fn column_to_str(column: ColumnData<'static>) -> DbResult<String> {
+    ColumnFormat::default().format(&column)
+}
 
-        _ => unimplemented!()
+/// if a column is NULL, regardless of datatype
+pub fn is_null(column: &ColumnData) -> bool {
+    use ColumnData::*;
+    match column {
+        U8(n) => n.is_none(),
+        I16(n) => n.is_none(),
+        I32(n) => n.is_none(),
+        I64(n) => n.is_none(),
+        F32(n) => n.is_none(),
+        F64(n) => n.is_none(),
+        Bit(b) => b.is_none(),
+        String(s) => s.is_none(),
+        Guid(g) => g.is_none(),
+        Binary(b) => b.is_none(),
+        Numeric(n) => n.is_none(),
+        Xml(x) => x.is_none(),
+        DateTime(dt) => dt.is_none(),
+        SmallDateTime(dt) => dt.is_none(),
+        Time(t) => t.is_none(),
+        Date(d) => d.is_none(),
+        DateTime2(dt) => dt.is_none(),
+        DateTimeOffset(dt) => dt.is_none(),
     }
 }
 
 /// Converts a SQL row to a tab-delimited string
-pub fn row_to_string(row: Row) -> String {
-    row.into_iter()
-        .map(column_to_str)
-
-        // generally speaking, one the these lines should be no more than 128 characters
-        //  so we set an initial capacity of 128 to try to avoid reallocations
-        .fold(String::with_capacity(128), |acc, s| acc + &s + "\t")
-        
-        .trim_end() // remove trailing '\t'
-        .into()
+pub fn row_to_string(row: Row) -> DbResult<String> {
+    Ok(fields_to_string(&row_to_strings(row)?))
+}
+
+/// Converts each column of a SQL row to a string
+pub fn row_to_strings(row: Row) -> DbResult<Vec<String>> {
+    Record::from(row).into_strings()
+}
+
+/// Joins fields into a tab-delimited string
+pub fn fields_to_string(fields: &[String]) -> String {
+    let mut result = fields.join("\t");
+
+    // remove trailing whitespace (and empty trailing fields)
+    result.truncate(result.trim_end().len());
+
+    result
+}
+
+/// get a column value that must not be NULL
+pub fn required<'a, R: FromSql<'a>>(row: &'a Row, column: &str) -> DbResult<R> {
+    optional(row, column)?
+        .ok_or_else(|| Error::NullValue(column.into()))
+}
+
+/// get a column value that may be NULL
+pub fn optional<'a, R: FromSql<'a>>(row: &'a Row, column: &str) -> DbResult<Option<R>> {
+    if !row.columns().iter().any(|col| col.name() == column) {
+        return Err(Error::MissingColumn(column.into()));
+    }
+
+    Ok(row.try_get(column)?)
 }
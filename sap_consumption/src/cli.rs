@@ -2,9 +2,9 @@
 use clap::{Parser, Subcommand};
 use clap_verbosity_flag::{Verbosity, InfoLevel};
 use std::path::PathBuf;
-use sysinteg_core::config::TomlConfig;
+use sysinteg_core::config::{ConfigLoader, LoadedConfig, TomlConfig, Validate};
 
-use crate::config::{CONFIG_FILE, SapConsumptionConfig};
+use crate::config::{APP_NAME, CONFIG_FILE, SapConsumptionConfig};
 
 #[derive(Debug, Parser)]
 #[command(author, version, about)]
@@ -12,6 +12,14 @@ pub struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
     
+    /// config file (defaults to the first `config.toml` next to the executable, in the working directory or in the user config directory)
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+
+    /// override a config value (i.e. `--set database.server=<server>`)
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    overrides: Vec<String>,
+
     #[command(flatten)]
     pub verbose: Verbosity<InfoLevel>,
 }
@@ -23,13 +31,35 @@ enum Command {
     /// uninstall (deregister from the Windows Event Log)
     Uninstall,
     /// generate example config
-    GenerateConfig,
+    GenerateConfig {
+        /// add missing keys to the config file that is loaded (or `--config`), keeping the values that are set
+        #[arg(long)]
+        merge: bool,
+    },
+    /// show the effective config and where each value came from
+    ShowConfig,
+    /// check the config for placeholders and invalid values
+    CheckConfig,
+    /// cost center issue rules
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RulesCommand {
+    /// show which account rule a part name matches
+    Test {
+        /// part name
+        part: String
+    },
 }
 
 impl Cli {
     pub fn handle_install(&self) -> anyhow::Result<bool> {
         let log_app_name = || -> anyhow::Result<String> {
-            let cfg = SapConsumptionConfig::load(&PathBuf::from(CONFIG_FILE))?;
+            let cfg = self.load_config()?.into_inner();
             
             Ok(cfg.logging_name)
         };
@@ -38,7 +68,32 @@ impl Cli {
             match command {
                 Command::Install   => eventlog::register(&log_app_name()?)?,
                 Command::Uninstall => eventlog::deregister(&log_app_name()?)?,
-                Command::GenerateConfig => SapConsumptionConfig::generate(&PathBuf::from(CONFIG_FILE))?,
+                Command::GenerateConfig { merge } => match merge {
+                    // merge into the config file that is loaded
+                    true => {
+                        let path = self.loader().find_local_file()?;
+
+                        match SapConsumptionConfig::merge(&path)?.as_slice() {
+                            []    => println!("{} is up to date", path.display()),
+                            added => println!("added to {}: {}", path.display(), added.join(", ")),
+                        }
+                    },
+                    false => SapConsumptionConfig::generate(self.config.clone().unwrap_or_else(|| PathBuf::from(CONFIG_FILE)))?,
+                },
+                Command::ShowConfig => println!("{}", self.load_config()?.explain()),
+                Command::CheckConfig => {
+                    self.load_config()?.config.validate()?;
+
+                    println!("config is valid");
+                },
+                Command::Rules { command: RulesCommand::Test { part } } => {
+                    let rules = self.load_config()?.into_inner().cost_center;
+
+                    match rules.matching_rule(part) {
+                        Some((rule, matched)) => println!("`{part}` matches rule `{}` by {matched} (account {})", rule.name(), rule.account()),
+                        None => println!("`{part}` does not match any rule (default account {})", rules.default_account())
+                    }
+                },
             };
 
             // false -> do not run executable
@@ -50,6 +105,20 @@ impl Cli {
 
     }
 
+    /// load the config in layers (defaults, config files, environment and `--set` overrides)
+    pub fn load_config(&self) -> anyhow::Result<LoadedConfig<SapConsumptionConfig>> {
+        SapConsumptionConfig::load_layered(&self.loader())
+    }
+
+    fn loader(&self) -> ConfigLoader {
+        ConfigLoader::new(APP_NAME)
+            .local_file(CONFIG_FILE)
+            .default_value("database.application_name", APP_NAME)
+            .config_file(self.config.as_ref())
+            .require_file()
+            .overrides(&self.overrides)
+    }
+
     pub fn log_level_filter(&self) -> log::LevelFilter {
         self.verbose.log_level_filter()
     }
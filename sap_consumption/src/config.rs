@@ -3,26 +3,110 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use sysinteg_core::config::TomlConfig;
-use sysinteg_db::DbConnParams;
+use sysinteg_core::api::{PlantMap, UnitOfMeasure};
+use sysinteg_core::config::{TomlConfig, Validate, Validation};
+use sysinteg_db::{ColumnFormat, DbConnParams};
 
+use crate::rules::IssueRules;
+
+/// name of the system and user config files (`sap_consumption.toml`)
+pub const APP_NAME: &str = "sap_consumption";
 pub const CONFIG_FILE: &str = "config.toml";
 
+sysinteg_core::documented_config! {
+    #[derive(Debug, Deserialize, Serialize)]
+    pub struct SapConsumptionConfig {
+        /// Sigmanest database server and database name
+        pub database: DbConnParams,
+        /// network path to where the `.ready` files are written
+        pub output_dir: PathBuf,
+        /// application name used for logging to the Windows Event Log
+        pub logging_name: String,
+        /// machine name patterns and the plant they are in
+        ///
+        /// patterns are regexes that are checked in order, and the first match is used.
+        /// add a last pattern of `.` to map every other machine to a plant
+        pub plants: PlantMap,
+        /// rules for issuing parts to a cost center (`20xx` shipments)
+        ///
+        /// rules are checked in order and the first rule whose `tokens` or `pattern` match the part name is used.
+        /// `shipments` maps a year shipment to its cost center (i.e. `2024 = "<cost center>"`)
+        pub cost_center: IssueRules,
+        /// unit of measure that material is output in, for each dataset (`IN2`, `FT2` or `LB`)
+        #[serde(default)]
+        pub material_uom: DatasetUnits,
+        /// how column values are written (date formats, decimal places, bit values and NULL)
+        ///
+        /// rows with a NULL column are logged and left out of the dataset, unless `null` is set
+        #[serde(default)]
+        pub format: ColumnFormat,
+    }
+
+    nested { database: DbConnParams }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
-pub struct SapConsumptionConfig {
-    pub database: DbConnParams,
-    pub output_dir: PathBuf,
-    pub logging_name: String,
+pub struct DatasetUnits {
+    pub production: UnitOfMeasure,
+    pub issue: UnitOfMeasure,
+}
+
+impl Default for DatasetUnits {
+    fn default() -> Self {
+        Self {
+            production: UnitOfMeasure::SquareInch,
+            issue: UnitOfMeasure::SquareInch,
+        }
+    }
 }
 
 impl TomlConfig for SapConsumptionConfig {}
 
+impl Validate for SapConsumptionConfig {
+    fn validate_into(&self, report: &mut Validation) {
+        report
+            .nested("database", &self.database)
+            .writable_dir("output_dir", &self.output_dir)
+            .required("logging_name", &self.logging_name)
+            .nested("plants", &self.plants)
+            .nested("cost_center", &self.cost_center)
+            .nested("format", &self.format);
+    }
+}
+
 impl Default for SapConsumptionConfig {
     fn default() -> Self {
         Self {
             database: DbConnParams::default(),
             output_dir: PathBuf::from(r"\\<server>\<path to where .ready files are placed>"),
-            logging_name: String::from("<application name used for logging to the Windows Event Log>")
+            logging_name: String::from("<application name used for logging to the Windows Event Log>"),
+            plants: default_plants(),
+            cost_center: IssueRules::default(),
+            material_uom: DatasetUnits::default(),
+            format: ColumnFormat::default(),
         }
     }
 }
+
+fn default_plants() -> PlantMap {
+    let mut plants = PlantMap::default();
+    // unwraps are safe here because the pattern and plant are known to be valid
+    plants.push("^Plant_3", "HS02".parse().unwrap()).unwrap();
+
+    plants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_docs() {
+        let toml = sysinteg_core::config::annotated(&SapConsumptionConfig::default()).unwrap();
+
+        // fields of the database connection are documented, and options that are not set are commented out
+        assert!(toml.contains("[database]\n# Server name\nserver = "), "{toml}");
+        assert!(toml.contains("# Seconds to wait for each query\n# command_timeout =\n"), "{toml}");
+        assert!(toml.contains("# Connection pool size and health checks (see `DbPool`)\n[database.pool]"), "{toml}");
+    }
+}
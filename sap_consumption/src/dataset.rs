@@ -1,15 +1,29 @@
 // tokio has asyncronous file operations,
 //  but we don't need to use them since file
 //  operations are done on the main thread.
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::Write;
 
 use std::path::PathBuf;
 
 // use tiberius::Result;
-use sysinteg_db::{DbClient, DbResult};
+use sysinteg_core::api::{IssueCode, Quantity, UnitOfMeasure};
+use sysinteg_db::{SapDataset, SigmanestStore};
 
+use crate::config::SapConsumptionConfig;
 
+/// column that has the machine name, which is replaced with the plant
+const MACHINE_COLUMN: &str = "MachineName";
+/// columns used to classify issues (part name, job, shipment and sheet WBS element)
+const ISSUE_COLUMNS: [&str; 4] = ["PartName", "Job", "Shipment", "MaterialWbs"];
+/// columns used to convert the material quantity (quantity, unit of measure and plate thickness)
+///
+/// the thickness column is not output
+const MATERIAL_COLUMNS: [&str; 3] = ["TotalNestedArea", "MaterialUoM", "MaterialThickness"];
+
+
+#[derive(Debug, Clone, Copy)]
 pub enum Dataset {
     Production,
     Issue
@@ -23,10 +37,10 @@ impl Dataset {
         }
     }
 
-    fn query(&self) -> &str {
+    fn material_uom(&self, config: &SapConsumptionConfig) -> UnitOfMeasure {
         match self {
-            Self::Production => "EXEC SapProductionData_SinceLastRun @End = @P1",
-            Self::Issue      => "EXEC SapIssueData_SinceLastRun @End = @P1",
+            Self::Production => config.material_uom.production,
+            Self::Issue      => config.material_uom.issue,
         }
     }
 
@@ -37,45 +51,269 @@ impl Dataset {
         filename
     }
 
-    pub async fn pull_data(self, client: &mut DbClient, end: chrono::NaiveDateTime, output_dir: &PathBuf) -> DbResult<()> {
+    pub async fn pull_data(self, store: &impl SigmanestStore, end: chrono::NaiveDateTime, config: &SapConsumptionConfig) -> anyhow::Result<()> {
         let name = self.name();
 
         log::trace!("pulling dataset `{}`", name);
-        let data = client.query(self.query(), &[&end]).await?
-            .into_first_result().await?;
+        let data = store.dataset(self.into(), end).await?;
 
         if data.len() == 0 {
             log::info!("Dataset `{}` is empty", name);
         } else {
-            // TODO: store on server for verification once feedback loop from SAP is established
-            let filename = self.filename(end, output_dir);
-            let mut file = File::create(&filename)
-                .map_err(|error| {
-                    log::error!("Failed to create {} file {}", name, &filename.to_str().unwrap());
+            // all rows have the same columns
+            let columns = data[0].columns().to_vec();
+            let column = |name: &str| columns.iter().position(|col| col == name);
+            let machine_column = column(MACHINE_COLUMN);
+
+            // part name, job, shipment and sheet WBS element are needed to classify issues
+            let issue_columns = match self {
+                Self::Production => None,
+                Self::Issue => match ISSUE_COLUMNS.map(column) {
+                    [Some(part), Some(job), Some(shipment), Some(wbs)] => Some([part, job, shipment, wbs]),
+                    _ => anyhow::bail!("dataset `{name}` is missing one of the columns {ISSUE_COLUMNS:?}")
+                }
+            };
+
+            let material_uom = self.material_uom(config);
+            let [qty_column, uom_column, thickness_column] = match MATERIAL_COLUMNS.map(column) {
+                [Some(qty), Some(uom), Some(thickness)] => [qty, uom, thickness],
+                _ => anyhow::bail!("dataset `{name}` is missing one of the columns {MATERIAL_COLUMNS:?}")
+            };
+
+            // invalid rows and rows for unknown machines are logged and left out of the file
+            let mut skipped = 0;
+            let mut unknown_machines = BTreeSet::new();
+            let mut records = Vec::with_capacity(data.len());
+            for row in data {
+                let mut fields = match row.into_strings_with(&config.format) {
+                    Ok(fields) => fields,
+                    Err(error) => {
+                        log::error!("Skipping invalid row in dataset `{}`: {}", name, error);
+                        skipped += 1;
+                        continue;
+                    }
+                };
+
+                // replace machine name with the plant it is in
+                if let Some(i) = machine_column {
+                    match config.plants.plant(&fields[i]) {
+                        Some(plant) => fields[i] = plant.to_string(),
+                        None => {
+                            unknown_machines.insert(fields[i].clone());
+                            skipped += 1;
+                            continue;
+                        }
+                    }
+                }
+
+                // replace part name, job and shipment with the issue `Code`, `User1` and `User2`
+                if let Some([part, job, shipment, wbs]) = issue_columns {
+                    let issue = IssueCode::classify(&fields[part], &fields[job], &fields[shipment], &fields[wbs], &config.cost_center);
+                    fields[part] = issue.code.to_string();
+                    fields[job] = issue.user1;
+                    fields[shipment] = issue.user2;
+                }
+
+                // convert material quantity to the dataset's unit of measure
+                match convert_material(&fields[qty_column], &fields[uom_column], &fields[thickness_column], material_uom) {
+                    Ok(Some(quantity)) => {
+                        fields[qty_column] = format!("{:.3}", quantity.value());
+                        fields[uom_column] = quantity.uom().to_string();
+                    },
+                    Ok(None) => (),
+                    Err(error) => {
+                        log::error!("Skipping row with an invalid material quantity in dataset `{}`: {}", name, error);
+                        skipped += 1;
+                        continue;
+                    }
+                }
+                fields.remove(thickness_column);
+
+                records.push(sysinteg_db::fields_to_string(&fields));
+            }
+
+            if !unknown_machines.is_empty() {
+                let machines = unknown_machines.into_iter().collect::<Vec<_>>().join(", ");
+                log::error!("Skipping rows in dataset `{}` for machines that are not mapped to a plant: {}", name, machines);
+            }
+
+            if skipped > 0 {
+                log::error!("{} rows of dataset `{}` were skipped", skipped, name);
+            }
+
+            if records.is_empty() {
+                log::warn!("Dataset `{}` has no valid rows", name);
+            } else {
+                // TODO: store on server for verification once feedback loop from SAP is established
+                let filename = self.filename(end, &config.output_dir);
+                let mut file = File::create(&filename)
+                    .map_err(|error| {
+                        log::error!("Failed to create {} file {}", name, &filename.to_str().unwrap());
                 
-                    error
-                })?;
+                        error
+                    })?;
         
-            log::trace!("Writing dataset `{}`", name);
-            let file_contents = data
-                .into_iter()
-                // convert row to tab delimited string
-                .map(|row| sysinteg_db::row_to_string(row))
-                .collect::<Vec<String>>()
-                .join("\n");
+                log::trace!("Writing dataset `{}`", name);
+                let file_contents = records.join("\n");
     
-            file.write_all(file_contents.as_bytes())
-                .map_err(|error| {
-                    log::error!("failed to write dataset `{}` to {}. Deleting file.", name, &filename.to_str().unwrap());
-                    let _ = std::fs::remove_file(filename);
+                file.write_all(file_contents.as_bytes())
+                    .map_err(|error| {
+                        log::error!("failed to write dataset `{}` to {}. Deleting file.", name, &filename.to_str().unwrap());
+                        let _ = std::fs::remove_file(filename);
 
-                    error
-                })?;
+                        error
+                    })?;
+            }
         }
 
         // update last runtime 
-        client.execute("UPDATE HighSteel.RuntimeInfo SET last_runtime=@P1 WHERE name=@P2", &[&end, &format!("Sap{}Data", name)]).await?;
+        store.set_last_runtime(self.into(), end).await?;
             
         Ok(())
     }
 }
+
+impl From<Dataset> for SapDataset {
+    fn from(dataset: Dataset) -> Self {
+        match dataset {
+            Dataset::Production => Self::Production,
+            Dataset::Issue => Self::Issue,
+        }
+    }
+}
+
+/// material quantity converted to `to`, or `None` if it is already in that unit of measure
+fn convert_material(qty: &str, uom: &str, thickness: &str, to: UnitOfMeasure) -> anyhow::Result<Option<Quantity>> {
+    let uom: UnitOfMeasure = uom.parse()?;
+    if uom == to {
+        return Ok(None);
+    }
+
+    let thickness = match thickness.trim() {
+        "" => None,
+        thickness => Some(thickness.parse()?)
+    };
+
+    Ok(Some(Quantity::new(qty.parse()?, uom).convert(to, thickness)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinteg_db::MemoryStore;
+
+    const FIXTURE: &str = r#"
+        [[production]]
+        MachineName = "Plant_3_Gemini"
+        Program = "52198"
+        TotalNestedArea = 1440.0
+        MaterialUoM = "IN2"
+        MaterialThickness = 0.5
+
+        [[issue]]
+        MachineName = "Plasma_1"
+        PartName = "1200123A-X1"
+        Job = "1200123"
+        Shipment = "01"
+        MaterialWbs = ""
+        TotalNestedArea = 1440.0
+        MaterialUoM = "IN2"
+        MaterialThickness = 0.5
+    "#;
+
+    fn setup(test: &str) -> (MemoryStore, SapConsumptionConfig, chrono::NaiveDateTime) {
+        let output_dir = std::env::temp_dir().join(format!("sap_consumption_{test}"));
+        let _ = std::fs::remove_dir_all(&output_dir);
+        std::fs::create_dir_all(&output_dir).unwrap();
+
+        let config = SapConsumptionConfig { output_dir, ..Default::default() };
+
+        (MemoryStore::from_toml(FIXTURE).unwrap(), config, "2024-01-31T15:00:00".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_pull_data() {
+        let (store, config, end) = setup("pull_data");
+
+        Dataset::Production.pull_data(&store, end, &config).await.unwrap();
+
+        // machine is replaced with its plant and the thickness is not output
+        let contents = std::fs::read_to_string(config.output_dir.join("Production_20240131150000.ready")).unwrap();
+        assert_eq!(contents, "HS02\t52198\t1440\tIN2");
+        assert_eq!(store.last_runtime(SapDataset::Production).await.unwrap(), Some(end));
+    }
+
+    #[tokio::test]
+    async fn test_issue_columns() {
+        let (_, config, end) = setup("issue_columns");
+        let store = MemoryStore::from_toml(r#"
+            [[issue]]
+            MachineName = "Plant_3_Gemini"
+            PartName = "1200123A-X1"
+            Job = "1200123"
+            Shipment = "01"
+            MaterialWbs = "D-1200123-00123"
+            TotalNestedArea = 1440.0
+            MaterialUoM = "IN2"
+            MaterialThickness = 0.5
+        "#).unwrap();
+
+        // part name, job and shipment are replaced in place, even if they are not the first columns
+        Dataset::Issue.pull_data(&store, end, &config).await.unwrap();
+        let contents = std::fs::read_to_string(config.output_dir.join("Issue_20240131150000.ready")).unwrap();
+        assert_eq!(contents, "HS02\tPR01\tD-1200123\t01\tD-1200123-00123\t1440\tIN2");
+    }
+
+    #[tokio::test]
+    async fn test_null_column() {
+        let (_, mut config, end) = setup("null_column");
+        let store = MemoryStore::from_json(r#"{ "production": [
+            { "MachineName": "Plant_3_Gemini", "Mill": null, "TotalNestedArea": 1440.0, "MaterialUoM": "IN2", "MaterialThickness": 0.5 },
+            { "MachineName": "Plant_3_Gemini", "Mill": "Nucor", "TotalNestedArea": 720.0, "MaterialUoM": "IN2", "MaterialThickness": 0.5 }
+        ] }"#).unwrap();
+        let filename = config.output_dir.join("Production_20240131150000.ready");
+
+        // row with a NULL column is left out, and the rest of the dataset is written
+        Dataset::Production.pull_data(&store, end, &config).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&filename).unwrap(), "HS02\tNucor\t720\tIN2");
+        assert_eq!(store.last_runtime(SapDataset::Production).await.unwrap(), Some(end));
+
+        config.format.null = Some(String::new());
+        Dataset::Production.pull_data(&store, end, &config).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&filename).unwrap(), "HS02\t\t1440\tIN2\nHS02\tNucor\t720\tIN2");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_material() {
+        let (_, config, end) = setup("invalid_material");
+        let store = MemoryStore::from_toml(r#"
+            [[production]]
+            MachineName = "Plant_3_Gemini"
+            TotalNestedArea = 1440.0
+            MaterialUoM = "EA"
+            MaterialThickness = 0.5
+
+            [[production]]
+            MachineName = "Plant_3_Gemini"
+            TotalNestedArea = 720.0
+            MaterialUoM = "IN2"
+            MaterialThickness = 0.5
+        "#).unwrap();
+
+        // invalid row is left out, and the runtime is updated
+        Dataset::Production.pull_data(&store, end, &config).await.unwrap();
+        let contents = std::fs::read_to_string(config.output_dir.join("Production_20240131150000.ready")).unwrap();
+        assert_eq!(contents, "HS02\t720\tIN2");
+        assert_eq!(store.last_runtime(SapDataset::Production).await.unwrap(), Some(end));
+    }
+
+    #[tokio::test]
+    async fn test_unmapped_machine() {
+        let (store, config, end) = setup("unmapped_machine");
+
+        // only row is left out, so no file is written
+        Dataset::Issue.pull_data(&store, end, &config).await.unwrap();
+        assert!(!config.output_dir.join("Issue_20240131150000.ready").exists());
+        assert_eq!(store.last_runtime(SapDataset::Issue).await.unwrap(), Some(end));
+    }
+}
@@ -1,7 +1,7 @@
 
 //! logging framework
 
-use chrono::{Local, NaiveDateTime};
+use chrono::Local;
 use eventlog::EventLog;
 use log::{Level, Log, LevelFilter};
 
@@ -15,15 +15,7 @@ const CONTROLLER_TARGET: &str = "LOGGING_CONTROLLER";
 #[derive(Debug)]
 enum Message {
     Shutdown,
-    Message(LogMessage)
-}
-
-#[derive(Debug)]
-struct LogMessage {
-    timestamp: NaiveDateTime,
-    app: String,
-    level: Level,
-    message: String
+    Message(LogEntry)
 }
 
 // TODO: move to sysinteg-db
@@ -37,13 +29,13 @@ pub struct MssqlDbLogger {
 }
 
 impl MssqlDbLogger {
-    /// create a new database logger
-    pub fn new(conn_params: &DbConnParams, level: Level) -> Self {
+    /// create a new database logger, which shares connections from `pool`
+    pub fn new(pool: &DbPool, level: Level) -> Self {
         let (tx, rx) = mpsc::channel(32);
 
-        let params = conn_params.clone();
+        let pool = pool.clone();
         let worker = Some(
-            tokio::spawn(async move { DbLoggerWorker::run(params, rx).await })
+            tokio::spawn(async move { DbLoggerWorker::run(pool, rx).await })
         );
 
         Self { worker, tx, level }
@@ -76,7 +68,7 @@ impl Log for MssqlDbLogger {
                 }
             },
             msg => {
-                let payload = Message::Message(LogMessage {
+                let payload = Message::Message(LogEntry {
                     timestamp: Local::now().naive_local(),
                     app: record.target().into(),
                     level: record.level(),
@@ -96,29 +88,27 @@ impl Log for MssqlDbLogger {
 struct DbLoggerWorker {}
 
 impl DbLoggerWorker {
-    pub async fn run(params: DbConnParams, mut rx: Receiver<Message>) {
-        let client = params.connect().await;
-
-        match client {
-            Ok(mut client) => {
-                while let Some(msg) = rx.recv().await {
-                    match msg {
-                        Message::Shutdown => break,
-                        Message::Message(msg) => {
-                            // we will just ignore failures for simplicity
-                            let _ = client.execute(
-                                "INSERT INTO HighSteel.Log(timestamp, app, level, message) VALUES(@P1, @P2, @P3, @P4)",
-                                &[&msg.timestamp, &msg.app, &msg.level.as_str(), &msg.message]
-                            ).await;
-                        }
+    pub async fn run(store: impl SigmanestStore, mut rx: Receiver<Message>) {
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                Message::Shutdown => break,
+                Message::Message(msg) => {
+                    // connections are only held while writing, so they can be used by the datasets
+                    match store.log(&msg).await {
+                        Ok(()) => (),
+
+                        // although this won't hit the database logger, it might hit other active loggers
+                        Err(e) if e.is_transient() => {
+                            std::thread::sleep(std::time::Duration::from_secs(2));    
+                            log::warn!("Failed to connect to database for logging");
+
+                            break;
+                        },
+
+                        // we will just ignore other failures for simplicity
+                        Err(_) => ()
                     }
                 }
-            },
-
-            // although this won't hit the database logger, it might hit other active loggers
-            _ => {
-                std::thread::sleep(std::time::Duration::from_secs(2));    
-                log::warn!("Failed to connect to database for logging")
             }
         }
 
@@ -139,7 +129,7 @@ pub struct EventAndDbLogger {
 
 impl EventAndDbLogger {
     /// initialize the loggers
-    pub async fn init<T, I>(name: &str, dbcfg: &DbConnParams, level: LevelFilter, addl_modules: T) -> anyhow::Result<Self>
+    pub async fn init<T, I>(name: &str, pool: &DbPool, level: LevelFilter, addl_modules: T) -> anyhow::Result<Self>
         where
             I: ToString,
             T: IntoIterator<Item = I>
@@ -149,7 +139,7 @@ impl EventAndDbLogger {
 
         // create loggers
         let event_logger = EventLog::new(name, loggers_cfg_level)?;
-        let mut db_logger = MssqlDbLogger::new(dbcfg, loggers_cfg_level);
+        let mut db_logger = MssqlDbLogger::new(pool, loggers_cfg_level);
 
         // get handle to the `tokio::spawn` task MssqlDbLogger worker is running in
         let worker = db_logger.take_worker();
@@ -164,7 +154,10 @@ impl EventAndDbLogger {
             // Dependencies such as Tokio/Tiberius do some logging of their own.
             //  This allows us to use a lower log level, while silencing their verbose logs.
             .level(log::LevelFilter::Error)
-            .level_for("sysinteg", level);
+            // fern matches whole `::` separated module paths, so each crate is listed (i.e. `sysinteg_db::retry` warnings)
+            .level_for("sysinteg", level)
+            .level_for("sysinteg_core", level)
+            .level_for("sysinteg_db", level);
 
         for module in addl_modules {
             logger = logger
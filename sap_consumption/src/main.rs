@@ -6,15 +6,16 @@ mod cli;
 mod config;
 mod dataset;
 mod logging;
+mod rules;
 
 use chrono::{Local, NaiveDateTime, NaiveTime, Timelike};
 use clap::Parser;
-use std::path::PathBuf;
 
-use config::{CONFIG_FILE, SapConsumptionConfig};
+use config::SapConsumptionConfig;
 use dataset::Dataset;
-use sysinteg_core::config::TomlConfig;
 use logging::EventAndDbLogger;
+use sysinteg_core::config::Validate;
+use sysinteg_db::DbPool;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -22,13 +23,17 @@ async fn main() -> anyhow::Result<()> {
     
     if args.handle_install()? {
         // load config
-        let config = SapConsumptionConfig::load(&PathBuf::from(CONFIG_FILE))?;
+        let config = args.load_config()?.into_inner();
+        config.validate()?;
+
+        // connections are shared between the datasets and the database logger
+        let pool = DbPool::new(config.database.clone());
 
         // init logging
-        let logger = EventAndDbLogger::init(&config.logging_name, &config.database, args.log_level_filter(), &[module_path!()]).await?;
+        let logger = EventAndDbLogger::init(&config.logging_name, &pool, args.log_level_filter(), &[module_path!()]).await?;
         
         // pull data
-        pull_interval(config).await?;
+        pull_interval(config, &pool).await?;
 
         // clean up logger
         logger.finalize().await;
@@ -37,15 +42,15 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn pull_interval(config: SapConsumptionConfig) -> anyhow::Result<()> {
+async fn pull_interval(config: SapConsumptionConfig, pool: &DbPool) -> anyhow::Result<()> {
     let now = Local::now();
     let end = NaiveDateTime::new(now.date_naive(), NaiveTime::from_hms_opt(now.hour(), 0, 0).unwrap());
 
     log::info!("pulling data from last run until {}", end.format("%d/%m/%Y %H:%M"));
 
-    let mut client = config.database.connect().await?;
-    Dataset::Production.pull_data(&mut client, end, &config.output_dir).await?;
-    Dataset::Issue.pull_data(&mut client, end, &config.output_dir).await?;
+    // queries are retried by the pool, if they fail because of a network error
+    Dataset::Production.pull_data(pool, end, &config).await?;
+    Dataset::Issue.pull_data(pool, end, &config).await?;
 
     Ok(())
 }
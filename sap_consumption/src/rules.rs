@@ -0,0 +1,260 @@
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use sysinteg_core::api::CostCenterRules;
+use sysinteg_core::config::{Validate, Validation};
+
+/// Rules for issuing parts to a cost center
+///
+/// Account rules are checked in order and the first rule that matches the part name is used.
+/// If no rule matches, the part is issued to the default account.
+/// ```toml
+/// [cost_center]
+/// default_account = "637118"
+///
+/// [[cost_center.rule]]
+/// name = "machine parts"
+/// account = "634124"
+/// tokens = ["gemini", "titan"]
+///
+/// [cost_center.shipments]
+/// 2024 = "<cost center>"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "RulesFile", into = "RulesFile")]
+pub struct IssueRules {
+    default_account: String,
+    rules: Vec<AccountRule>,
+    shipments: BTreeMap<String, String>,
+}
+
+/// Rule to issue parts to an account
+#[derive(Debug, Clone)]
+pub struct AccountRule {
+    name: String,
+    account: String,
+    tokens: Vec<String>,
+    pattern: Option<Regex>,
+}
+
+/// How a part name matched an [`AccountRule`]
+#[derive(Debug, PartialEq)]
+pub enum RuleMatch<'a> {
+    /// `-` or `_` separated segment of the part name (case insensitive)
+    Token(&'a str),
+    /// regex pattern
+    Pattern(&'a str),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RulesFile {
+    default_account: String,
+    #[serde(default)]
+    rule: Vec<RuleLine>,
+    #[serde(default)]
+    shipments: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RuleLine {
+    name: String,
+    account: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tokens: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+}
+
+impl IssueRules {
+    /// rule that a part name matches, and how it matched
+    pub fn matching_rule(&self, part: &str) -> Option<(&AccountRule, RuleMatch<'_>)> {
+        self.rules.iter()
+            .find_map(|rule| rule.matches(part).map(|matched| (rule, matched)))
+    }
+
+    /// account used when no rule matches
+    pub fn default_account(&self) -> &str {
+        &self.default_account
+    }
+}
+
+impl CostCenterRules for IssueRules {
+    fn account(&self, part: &str) -> &str {
+        match self.matching_rule(part) {
+            Some((rule, _)) => &rule.account,
+            None => &self.default_account
+        }
+    }
+
+    fn cost_center(&self, shipment: &str) -> Option<&str> {
+        self.shipments.get(shipment).map(String::as_str)
+    }
+}
+
+impl Validate for IssueRules {
+    fn validate_into(&self, report: &mut Validation) {
+        report.required("default_account", &self.default_account);
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            report.required(&format!("rule[{i}].account"), &rule.account);
+        }
+
+        for (shipment, cost_center) in &self.shipments {
+            report.required(&format!("shipments.{shipment}"), cost_center);
+        }
+    }
+}
+
+impl Default for IssueRules {
+    fn default() -> Self {
+        let tokens = ["gemini", "titan", "mg", "farley", "ficep"];
+
+        Self {
+            default_account: String::from("637118"),
+            rules: vec![AccountRule {
+                name: String::from("machine parts"),
+                account: String::from("634124"),
+                tokens: tokens.map(String::from).to_vec(),
+                pattern: None,
+            }],
+            shipments: BTreeMap::new(),
+        }
+    }
+}
+
+impl AccountRule {
+    /// name of the rule
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// account to issue parts to
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    fn matches(&self, part: &str) -> Option<RuleMatch<'_>> {
+        let token = part.split(['-', '_'])
+            .find_map(|segment| self.tokens.iter().find(|token| segment.eq_ignore_ascii_case(token)));
+
+        match (token, &self.pattern) {
+            (Some(token), _) => Some(RuleMatch::Token(token)),
+            (None, Some(pattern)) if pattern.is_match(part) => Some(RuleMatch::Pattern(pattern.as_str())),
+            _ => None
+        }
+    }
+}
+
+impl Display for RuleMatch<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Token(token) => write!(f, "token `{token}`"),
+            Self::Pattern(pattern) => write!(f, "pattern `{pattern}`"),
+        }
+    }
+}
+
+impl TryFrom<RulesFile> for IssueRules {
+    type Error = anyhow::Error;
+
+    fn try_from(value: RulesFile) -> Result<Self, Self::Error> {
+        let rules = value.rule.into_iter()
+            .map(|line| {
+                if line.tokens.is_empty() && line.pattern.is_none() {
+                    anyhow::bail!("account rule `{}` has no tokens or pattern", line.name);
+                }
+
+                let pattern = line.pattern
+                    .map(|pattern| Regex::new(&pattern)
+                        .map_err(|e| anyhow::anyhow!("account rule `{}` has an invalid pattern: {e}", line.name))
+                    )
+                    .transpose()?;
+
+                Ok(AccountRule { name: line.name, account: line.account, tokens: line.tokens, pattern })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { default_account: value.default_account, rules, shipments: value.shipments })
+    }
+}
+
+impl From<IssueRules> for RulesFile {
+    fn from(value: IssueRules) -> Self {
+        Self {
+            default_account: value.default_account,
+            rule: value.rules.into_iter()
+                .map(|rule| RuleLine {
+                    name: rule.name,
+                    account: rule.account,
+                    tokens: rule.tokens,
+                    pattern: rule.pattern.map(|pattern| pattern.to_string())
+                })
+                .collect(),
+            shipments: value.shipments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules() {
+        let rules = IssueRules::default();
+
+        for part in ["gemini_1", "PART-TITAN", "A-mg-2", "Farley", "x_FICEP_y"] {
+            assert_eq!(rules.account(part), "634124", "{part} should be a machine part");
+        }
+
+        for part in ["geminis-1", "MGX", "1234567A-X1A"] {
+            assert_eq!(rules.account(part), "637118", "{part} should be a shop supply");
+        }
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let rules: IssueRules = toml::from_str(r#"
+            default_account = "637118"
+
+            [[rule]]
+            name = "saw blades"
+            account = "634000"
+            pattern = "(?i)^blade"
+
+            [[rule]]
+            name = "machine parts"
+            account = "634124"
+            tokens = ["gemini"]
+
+            [shipments]
+            2024 = "1100"
+        "#).unwrap();
+
+        let (rule, matched) = rules.matching_rule("BLADE_GEMINI").unwrap();
+        assert_eq!(rule.name(), "saw blades");
+        assert_eq!(matched, RuleMatch::Pattern("(?i)^blade"));
+
+        assert_eq!(rules.matching_rule("x-gemini").unwrap().1, RuleMatch::Token("gemini"));
+        assert_eq!(rules.cost_center("2024"), Some("1100"));
+        assert_eq!(rules.cost_center("2025"), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(IssueRules::default().validate().is_ok());
+
+        let rules: IssueRules = toml::from_str("default_account = \"\"\n[shipments]\n2024 = \"<cost center>\"").unwrap();
+        let paths: Vec<_> = rules.validate().unwrap_err().0.into_iter().map(|e| e.path).collect();
+        assert_eq!(paths, ["default_account", "shipments.2024"]);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(toml::from_str::<IssueRules>("default_account = \"1\"\n[[rule]]\nname = \"a\"\naccount = \"2\"").is_err());
+        assert!(toml::from_str::<IssueRules>("default_account = \"1\"\n[[rule]]\nname = \"a\"\naccount = \"2\"\npattern = \"(\"").is_err());
+    }
+}
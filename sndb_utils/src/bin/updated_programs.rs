@@ -1,9 +1,7 @@
 
-use sndb_utils::{DisplayUpdate, ProgramInputHandler, QueryTableUi};
-use sndb_utils::{HEADER, Program};
-
-use sysinteg_core::config::TomlConfig;
-use sysinteg_db::DbConnParams;
+use sndb_utils::{spawn_lookups, ProgramInputHandler, QueryTableUi};
+use sndb_utils::{ConfigArgs, HEADER};
+use sysinteg_db::{DbPool, StatusQuery};
 
 use std::env;
 use std::sync::mpsc;
@@ -27,63 +25,54 @@ async fn main() -> anyhow::Result<()> {
         let _ = simplelog::WriteLogger::init(level, config, std::fs::File::create("updatedprograms.log").unwrap());
     }
     
-    let cfg = match (env::var("SndbServer"), env::var("SndbDatabase")) {
-        (Ok(server), Ok(database)) => DbConnParams { server, database },
-        _ => match DbConnParams::load("db.toml") {
-            Ok(config) => config,
-            Err(error) => {
-                eprintln!("Failed to parse `db.toml`");
-
-                // wait for input to keep console window open
-                println!("Press any key to exit...");
-                let _ = std::io::stdin().read_line(&mut String::new());
-
-                return Err(error)
-            }
-        }
-    };
-
-    let mut client = match cfg.connect().await {
+    let args = ConfigArgs::parse(env::args().skip(1))?;
+    let cfg = match args.load() {
         Ok(config) => config,
         Err(error) => {
-            eprintln!("Failed to connect to database: {}", error);
+            eprintln!("Failed to load config: {error}");
 
             // wait for input to keep console window open
             println!("Press any key to exit...");
             let _ = std::io::stdin().read_line(&mut String::new());
 
-            return Err(error.into())
+            return Err(error)
         }
     };
 
-    let (tx_db, rx_db) = mpsc::channel();
-    let (tx_display, rx_display) = mpsc::channel();
+    if args.show_config {
+        println!("{}", cfg.explain());
 
-    let respond_to = tx_display.clone();
-    tokio::spawn(async move {
-        while let Ok(program) = rx_db.recv() {
-            log::trace!("Program results frequested for `{}`", program);
-            let query_result = client.query("EXEC GetProgramStatus @ProgramName=@P1", &[&program]).await;
-
-            let message = match query_result {
-                Ok(rows) => {
-                    // send results to display thread
-                    match rows.into_row().await {
-                        Ok(Some(row)) => DisplayUpdate::DbResult(Program::from(&row)),
-                        Ok(None) => DisplayUpdate::Message(format!("Program `{}` not found", program)),
-                        Err(_) => DisplayUpdate::Message(String::from("Failed to get database results row"))
-                    }
-                },
-                Err(_) => DisplayUpdate::Message(String::from("Failed to get database result"))
-            };
-
-            let _ = respond_to.send(message);
-        }
+        return Ok(())
+    }
+
+    let cfg = cfg.into_inner();
+
+    let pool = DbPool::new(cfg);
+
+    // connect before the UI starts, so that connection errors are shown (the connection goes back to the pool)
+    if let Err(error) = pool.get().await {
+        eprintln!("Failed to connect to database: {}", error);
+
+        // wait for input to keep console window open
+        println!("Press any key to exit...");
+        let _ = std::io::stdin().read_line(&mut String::new());
+
+        return Err(error.into())
+    }
+
+    let (tx_db, rx_db) = mpsc::channel::<String>();
+    let (tx_display, rx_display) = mpsc::channel();
 
-        log::trace!("Database thread shutting down");
-    });
+    spawn_lookups(pool, parse_program, rx_db, tx_display.clone());
 
     QueryTableUi::new(&HEADER)
         .with_instructions(INSTRUCTIONS)
         .run_loop(&mut ProgramInputHandler::new(tx_db, tx_display), rx_display)
-}
\ No newline at end of file
+}
+
+/// only programs are looked up
+fn parse_program(value: &str) -> Result<StatusQuery, String> {
+    value.parse()
+        .map(StatusQuery::Program)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,90 @@
+
+use std::path::PathBuf;
+
+use sysinteg_core::config::{ConfigLoader, LoadedConfig, TomlConfig};
+use sysinteg_db::DbConnParams;
+
+/// name of the config files (i.e. `sndb.toml` in the system and user config folders)
+const APP_NAME: &str = "sndb";
+/// local config file
+const LOCAL_FILE: &str = "db.toml";
+
+/// config command line arguments
+///
+/// - `--config <file>` sets the config file (otherwise `db.toml` is searched for)
+/// - `--set key=value` overrides a config value (can be repeated)
+/// - `--show-config` prints the effective config and where each value came from
+#[derive(Debug, Default)]
+pub struct ConfigArgs {
+    config: Option<PathBuf>,
+    overrides: Vec<String>,
+    /// print the effective config instead of running
+    pub show_config: bool,
+}
+
+impl ConfigArgs {
+    /// parse the config arguments (excluding the executable name)
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--show-config" => parsed.show_config = true,
+                "--config" => match args.next() {
+                    Some(value) => parsed.config = Some(value.into()),
+                    None => anyhow::bail!("`--config` expects a file argument")
+                },
+                "--set" => match args.next() {
+                    Some(value) => parsed.overrides.push(value),
+                    None => anyhow::bail!("`--set` expects a `key=value` argument")
+                },
+                _ => match arg.strip_prefix("--set=") {
+                    Some(value) => parsed.overrides.push(value.into()),
+                    None => anyhow::bail!("unexpected argument `{arg}`")
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// load the database config
+    ///
+    /// the legacy `SndbServer` and `SndbDatabase` environment variables are still supported
+    pub fn load(&self) -> anyhow::Result<LoadedConfig<DbConnParams>> {
+        let loader = ConfigLoader::new(APP_NAME)
+            .local_file(LOCAL_FILE)
+            // these tools only read, so they can use a read replica
+            .default_value("read_only", "true")
+            .default_value("application_name", "sndb_utils")
+            .config_file(self.config.as_ref())
+            .env_alias("SndbServer", "server")
+            .env_alias("SndbDatabase", "database")
+            .overrides(&self.overrides);
+
+        DbConnParams::load_layered(&loader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<ConfigArgs> {
+        ConfigArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = args(&["--set", "server=a", "--set=database=b", "--show-config", "--config", "sndb.toml"]).unwrap();
+
+        assert_eq!(parsed.overrides, ["server=a", "database=b"]);
+        assert_eq!(parsed.config, Some(PathBuf::from("sndb.toml")));
+        assert!(parsed.show_config);
+
+        assert!(args(&["--set"]).is_err());
+        assert!(args(&["--config"]).is_err());
+        assert!(args(&["program"]).is_err());
+    }
+}
@@ -3,13 +3,15 @@
 #![feature(linked_list_cursors)]
 
 mod buffer;
+mod config;
 mod input;
+mod lookup;
 mod program;
-mod query;
 mod termui;
 
 pub use buffer::InputBuffer;
+pub use config::ConfigArgs;
 pub use input::ProgramInputHandler;
+pub use lookup::{lookup, spawn_lookups};
 pub use program::{Program, ProgramState, HEADER};
-pub use query::Query;
 pub use termui::{DisplayUpdate, InputHandler, QueryTableUi};
@@ -0,0 +1,148 @@
+use std::sync::mpsc::{Receiver, Sender};
+use sysinteg_db::{SigmanestStore, StatusQuery};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use super::{DisplayUpdate, Program};
+
+/// look up the status of a query, as an update for the display thread
+pub async fn lookup<S: SigmanestStore>(store: &S, query: &StatusQuery) -> DisplayUpdate<Program> {
+    match store.status(query).await {
+        Ok(Some(row)) => match Program::try_from(&row) {
+            Ok(result) => DisplayUpdate::DbResult(result),
+            Err(e) => DisplayUpdate::Message(format!("Failed to read results for `{}`: {e}", query.value()))
+        },
+        Ok(None) => DisplayUpdate::Message(format!("Program `{}` not found", query.value())),
+        Err(e) => DisplayUpdate::Message(format!("Failed to get database result: {e}"))
+    }
+}
+
+/// look up each input on its own task, and send the results to the display thread
+///
+/// `parse` turns an input into a query, or the message to display if it cannot be queried.
+/// Results are sent in the order the inputs were received, even if a later lookup finishes first.
+pub fn spawn_lookups<S>(store: S, parse: fn(&str) -> Result<StatusQuery, String>, rx: Receiver<String>, respond_to: Sender<DisplayUpdate<Program>>) -> JoinHandle<()>
+    where S: SigmanestStore + Clone + 'static
+{
+    let runtime = tokio::runtime::Handle::current();
+
+    // each lookup's result is awaited in input order before it is sent
+    let (pending_tx, mut pending_rx) = mpsc::unbounded_channel::<oneshot::Receiver<DisplayUpdate<Program>>>();
+    runtime.spawn(async move {
+        while let Some(result) = pending_rx.recv().await {
+            if let Ok(message) = result.await {
+                let _ = respond_to.send(message);
+            }
+        }
+    });
+
+    // receiving blocks, so it is not done on a runtime worker (lookups spawned from a blocked worker may not run)
+    tokio::task::spawn_blocking(move || {
+        while let Ok(value) = rx.recv() {
+            log::trace!("Results requested for `{}`", value);
+            let store = store.clone();
+            let (result_tx, result_rx) = oneshot::channel();
+            let _ = pending_tx.send(result_rx);
+
+            // lookups run concurrently (for SQL Server, each on its own pooled connection)
+            runtime.spawn(async move {
+                let message = match parse(&value) {
+                    Ok(query) => lookup(&store, &query).await,
+                    Err(e) => DisplayUpdate::Message(e)
+                };
+
+                let _ = result_tx.send(message);
+            });
+        }
+
+        log::trace!("Database thread shutting down");
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProgramState;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use sysinteg_db::MemoryStore;
+
+    const FIXTURE: &str = r#"
+        [[status]]
+        ProgramName = "52198"
+        Status = "Active"
+        Timestamp = "2024-01-31T14:30:00"
+        SheetName = "S12345"
+        MaterialMaster = "50-0008A"
+        Thickness = 0.5
+        Width = 96.0
+        Length = 240.0
+        RemainingArea = 1200.0
+
+        [[status]]
+        ProgramName = "52199"
+        Status = "Posted"
+        Timestamp = "2024-01-31T14:30:00"
+    "#;
+
+    #[tokio::test]
+    async fn test_lookup() {
+        let store = MemoryStore::from_toml(FIXTURE).unwrap();
+
+        let result = lookup(&store, &StatusQuery::try_from("52198").unwrap()).await;
+        assert!(matches!(result, DisplayUpdate::DbResult(Program { state: ProgramState::Active(_), .. })));
+
+        let result = lookup(&store, &StatusQuery::try_from("52200").unwrap()).await;
+        assert!(matches!(result, DisplayUpdate::Message(msg) if msg == "Program `52200` not found"));
+
+        // unexpected status
+        let result = lookup(&store, &StatusQuery::try_from("52199").unwrap()).await;
+        assert!(matches!(result, DisplayUpdate::Message(msg) if msg.starts_with("Failed to read results for `52199`")));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_spawn_lookups() {
+        let store = MemoryStore::from_toml(FIXTURE).unwrap();
+        let (tx_db, rx_db) = mpsc::channel();
+        let (tx_display, rx_display) = mpsc::channel();
+
+        let worker = spawn_lookups(store, |value| StatusQuery::try_from(value), rx_db, tx_display);
+
+        tx_db.send(String::from("52198")).unwrap();
+        let result = rx_display.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(result, DisplayUpdate::DbResult(program) if program.name.as_str() == "52198"));
+
+        tx_db.send(String::from("not a program")).unwrap();
+        let result = rx_display.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(result, DisplayUpdate::Message(_)));
+
+        // worker stops when the input handler is dropped
+        drop(tx_db);
+        worker.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_lookup_order() {
+        let store = MemoryStore::from_toml(FIXTURE).unwrap();
+        let (tx_db, rx_db) = mpsc::channel();
+        let (tx_display, rx_display) = mpsc::channel();
+
+        // first lookup finishes after the second
+        let parse = |value: &str| {
+            if value == "52198" {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+
+            StatusQuery::try_from(value)
+        };
+        let _worker = spawn_lookups(store, parse, rx_db, tx_display);
+
+        tx_db.send(String::from("52198")).unwrap();
+        tx_db.send(String::from("not a program")).unwrap();
+
+        let result = rx_display.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(result, DisplayUpdate::DbResult(program) if program.name.as_str() == "52198"));
+        let result = rx_display.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(result, DisplayUpdate::Message(_)));
+    }
+}
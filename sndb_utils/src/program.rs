@@ -1,51 +1,57 @@
 
 use chrono::NaiveDateTime;
 use comfy_table::{Cell, Color, Row};
-use sysinteg_core::api::{Sheet, Wbs};
+use sysinteg_core::api::{ProgramName, Sheet, SheetSize, Wbs};
+use sysinteg_db::Record;
 
-pub const HEADER: [&str; 8] = ["Program", "Status", "Timestamp", "SAP MM", "Heat Number", "PO Number", "SheetName", "Operator"];
+pub const HEADER: [&str; 9] = ["Program", "Status", "Timestamp", "SAP MM", "Heat Number", "PO Number", "Sheet Size", "SheetName", "Operator"];
 const DATE_FORMAT: &str = "%e.%b.%Y %k:%M %P";
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
-    pub name: String,
+    pub name: ProgramName,
     pub state: ProgramState,
     pub sheet: Sheet,
 }
 
-impl Into<Row> for Program {
-    fn into(self) -> Row {
-        // "Program", "Status", "Timestamp", "SAP MM", "Heat Number", "PO Number", "SheetName", "Operator"
+impl From<Program> for Row {
+    fn from(program: Program) -> Self {
+        // "Program", "Status", "Timestamp", "SAP MM", "Heat Number", "PO Number", "Sheet Size", "SheetName", "Operator"
+        let size = program.sheet.size.map(|size| size.to_string()).unwrap_or_default();
         let mut row = Row::new();
-        row.add_cell(Cell::new(self.name));
+        row.add_cell(Cell::new(&program.name));
         
-        match &self.state {
+        match &program.state {
             ProgramState::Active(timestamp) => {
                 row
                     .add_cell(Cell::new("Active").fg(Color::Blue))
                     .add_cell(Cell::new(timestamp.format(DATE_FORMAT).to_string()))
-                    .add_cell(Cell::new(self.sheet.mm))
+                    .add_cell(Cell::new(program.sheet.mm.map(|mm| mm.to_string()).unwrap_or_default()))
                     .add_cell(Cell::new(""))
                     .add_cell(Cell::new(""))
-                    .add_cell(Cell::new(self.sheet.name));
+                    .add_cell(Cell::new(&size))
+                    .add_cell(Cell::new(&program.sheet.name));
             },
             ProgramState::Deleted(timestamp) => {
                 row
                     .add_cell(Cell::new("Deleted").fg(Color::Red))
                     .add_cell(Cell::new(timestamp.format(DATE_FORMAT).to_string()))
-                    .add_cell(Cell::new(self.sheet.mm))
+                    .add_cell(Cell::new(program.sheet.mm.map(|mm| mm.to_string()).unwrap_or_default()))
                     .add_cell(Cell::new(""))
                     .add_cell(Cell::new(""))
-                    .add_cell(Cell::new(self.sheet.name));
+                    .add_cell(Cell::new(&size))
+                    .add_cell(Cell::new(&program.sheet.name));
             },
             ProgramState::Updated { timestamp, operator } => {
                 row
                     .add_cell(Cell::new("Updated").fg(Color::Green))
                     .add_cell(Cell::new(timestamp.format(DATE_FORMAT).to_string()))
-                    .add_cell(Cell::new(self.sheet.mm))
-                    .add_cell(Cell::new(self.sheet.heat))
-                    .add_cell(Cell::new(format!("{}", self.sheet.po)))
-                    .add_cell(Cell::new(self.sheet.name));
+                    .add_cell(Cell::new(program.sheet.mm.map(|mm| mm.to_string()).unwrap_or_default()))
+                    .add_cell(Cell::new(program.sheet.heat))
+                    .add_cell(Cell::new(program.sheet.po))
+                    .add_cell(Cell::new(&size))
+                    .add_cell(Cell::new(&program.sheet.name));
 
                 if let Some(operator) = operator {
                     row.add_cell(Cell::new(operator));
@@ -57,38 +63,53 @@ impl Into<Row> for Program {
     }
 }
 
-impl From<&tiberius::Row> for Program {
-    fn from(row: &tiberius::Row) -> Self {
-        let state = ProgramState::from(row);
+impl TryFrom<&Record> for Program {
+    type Error = sysinteg_db::Error;
+
+    fn try_from(row: &Record) -> Result<Self, Self::Error> {
+        let state = ProgramState::try_from(row)?;
+        let name = row.required("ProgramName")?;
+        let sheet_name = row.required("SheetName")?;
+        let mm = row.optional("MaterialMaster")?;
+        let size = match (row.optional("Thickness")?, row.optional("Width")?, row.optional("Length")?) {
+            (Some(thickness), Some(width), Some(length)) => Some(SheetSize { thickness, width, length }),
+            _ => None
+        };
+        let remaining_area = row.optional("RemainingArea")?;
 
         match state {
-            ProgramState::Updated { .. } => Self {
-                name: row.get::<&str, _>("ProgramName").unwrap().into(),
+            ProgramState::Updated { .. } => Ok(Self {
+                name,
                 state,
                 sheet: Sheet {
-                    name: row.get::<&str, _>("SheetName").unwrap().into(),
-                    mm: row.get::<&str, _>("MaterialMaster").unwrap_or_default().into(),
-                    heat: row.get::<&str, _>("HeatNumber").unwrap_or_default().into(),
-                    po: row.get::<&str, _>("PoNumber").unwrap_or_default().into(),
-                    wbs: row.get::<&str, _>("Wbs").map(|wbs| Wbs::try_from(wbs).unwrap())
+                    name: sheet_name,
+                    mm,
+                    heat: row.optional::<&str>("HeatNumber")?.unwrap_or_default().into(),
+                    po: row.optional::<&str>("PoNumber")?.unwrap_or_default().into(),
+                    // WBS elements are entered by hand, so they are parsed leniently
+                    wbs: row.optional::<&str>("Wbs")?.map(Wbs::parse_lenient).transpose()?,
+                    size,
+                    remaining_area
                 }
-            },
-            _ => Self {
-                name: row.get::<&str, _>("ProgramName").unwrap().into(),
+            }),
+            _ => Ok(Self {
+                name,
                 state,
                 sheet: Sheet {
-                    name: row.get::<&str, _>("SheetName").unwrap().into(),
-                    mm: row.get::<&str, _>("MaterialMaster").unwrap_or_default().into(),
-                    
-                    ..Default::default()
+                    mm,
+                    size,
+                    remaining_area,
+
+                    ..Sheet::new(sheet_name)
                 }
-            }
+            })
         }
     }
 }
 
 /// State of a program
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProgramState {
     /// Program is active
     Active (
@@ -111,15 +132,57 @@ pub enum ProgramState {
     },
 }
 
-impl From<&tiberius::Row> for ProgramState {
-    fn from(row: &tiberius::Row) -> Self {
-        let timestamp: NaiveDateTime = row.get("Timestamp").unwrap();
+impl TryFrom<&Record> for ProgramState {
+    type Error = sysinteg_db::Error;
 
-        match row.get::<&str, _>("Status").unwrap() {
-            "Active" => Self::Active(timestamp),
-            "Deleted" => Self::Deleted(timestamp),
-            "Updated" => Self::Updated { timestamp, operator: row.get::<&str, _>("Operator").map(Into::into) },
-            unmatched => panic!("Received unexpected program status `{}`", unmatched)
+    fn try_from(row: &Record) -> Result<Self, Self::Error> {
+        let timestamp: NaiveDateTime = row.required("Timestamp")?;
+
+        match row.required::<&str>("Status")? {
+            "Active" => Ok(Self::Active(timestamp)),
+            "Deleted" => Ok(Self::Deleted(timestamp)),
+            "Updated" => Ok(Self::Updated { timestamp, operator: row.optional::<&str>("Operator")?.map(Into::into) }),
+            unmatched => Err(sysinteg_db::Error::UnexpectedValue { column: String::from("Status"), value: unmatched.into() })
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_updated() {
+        let row: Record = serde_json::from_str(r#"{
+            "ProgramName": "52198-2A",
+            "Status": "Updated",
+            "Timestamp": "2024-01-31T14:30:00",
+            "Operator": null,
+            "SheetName": "S12345",
+            "MaterialMaster": "50-0008A",
+            "HeatNumber": "A1234",
+            "PoNumber": "4500012345",
+            "Wbs": "D-1234567-00123",
+            "Thickness": 0.5,
+            "Width": 96.0,
+            "Length": null,
+            "RemainingArea": null
+        }"#).unwrap();
+
+        let program = Program::try_from(&row).unwrap();
+        assert_eq!(program.name.as_str(), "52198-2A");
+        assert!(matches!(program.state, ProgramState::Updated { operator: None, .. }));
+        assert_eq!(program.sheet.heat, "A1234");
+        assert_eq!(program.sheet.wbs.unwrap().to_string(), "D-1234567-00123");
+
+        // size is only set if all dimensions are
+        assert_eq!(program.sheet.size, None);
+    }
+
+    #[test]
+    fn test_unexpected_status() {
+        let row: Record = serde_json::from_str(r#"{ "Status": "Posted", "Timestamp": "2024-01-31T14:30:00" }"#).unwrap();
+
+        assert!(matches!(ProgramState::try_from(&row), Err(sysinteg_db::Error::UnexpectedValue { value, .. }) if value == "Posted"));
+        assert!(Program::try_from(&row).is_err());
+    }
+}